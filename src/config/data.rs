@@ -0,0 +1,68 @@
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<String>,
+	pub storage_format: StorageFormat,
+	pub date_format: String,
+	pub color: bool,
+	pub picker: String,
+	pub default_list: String,
+	pub locale: String,
+	pub notifiers: Vec<NotifierKind>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub notify_command: Option<String>
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			storage: None,
+			storage_format: StorageFormat::Yaml,
+			date_format: "%d/%m/%y".to_string(),
+			color: true,
+			picker: "fzf".to_string(),
+			default_list: "todo".to_string(),
+			locale: "en-GB".to_string(),
+			notifiers: vec![NotifierKind::Desktop, NotifierKind::Terminal],
+			notify_command: None
+		}
+	}
+}
+
+impl Config {
+	// Timestamps are shown as the date followed by the time
+	pub fn timestamp_format(&self) -> String {
+		format!("{} %H:%M", self.date_format)
+	}
+
+	pub fn is_month_first(&self) -> bool {
+		self.locale.to_lowercase().ends_with("-us")
+	}
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+	Yaml,
+	Json,
+	Sqlite
+}
+
+impl std::fmt::Display for StorageFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			StorageFormat::Yaml => write!(f, "yaml"),
+			StorageFormat::Json => write!(f, "json"),
+			StorageFormat::Sqlite => write!(f, "sqlite")
+		}
+	}
+}
+
+#[derive(serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifierKind {
+	Desktop,
+	Terminal,
+	Command
+}
@@ -0,0 +1,150 @@
+use std::sync::OnceLock;
+
+use clap::{Args, Subcommand};
+use colored::Colorize;
+
+pub use data::{Config, NotifierKind, StorageFormat};
+
+use crate::{editor, paths, storage};
+
+#[derive(Args)]
+pub struct Command {
+	#[command(subcommand)]
+	command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+	/// Show the value of a key, or the whole config if no key is given
+	Get {
+		key: Option<String>
+	},
+	Set {
+		key: String,
+
+		#[arg(required = true)]
+		value: Vec<String>
+	},
+	/// Open the config in $EDITOR
+	Edit
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+// Loads the config used by the rest of hey, needs to be called before get
+pub fn load() -> anyhow::Result<()> {
+	let config = utils::read_config()?;
+	// Colors are still left out when the output isn't a terminal
+	if !config.color {
+		colored::control::set_override(false);
+	}
+	let _ = CONFIG.set(config);
+
+	Ok(())
+}
+
+// The loaded config, or the default config if it hasn't been loaded
+pub fn get() -> &'static Config {
+	CONFIG.get_or_init(Config::default)
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	match command.command {
+		Commands::Get{key} => get_command(key),
+		Commands::Set{key, value} => set_command(&key, &value.join(" ")),
+		Commands::Edit => edit_command()
+	}
+}
+
+fn get_command(key: Option<String>) -> anyhow::Result<()> {
+	let config = utils::read_config()?;
+	let keys = match key {
+		Some(key) => vec![check_key(&key)?],
+		None => utils::get_keys()
+	};
+
+	for key in keys {
+		match utils::get_value(&config, &key)? {
+			Some(value) => println!("{}: {}", key.bold(), value),
+			None => println!("{}: {}", key.bold(), "not set".dimmed())
+		}
+	}
+
+	Ok(())
+}
+
+fn set_command(key: &str, value: &str) -> anyhow::Result<()> {
+	let key = check_key(key)?;
+	let path = paths::get_config_path()?;
+
+	// Makes sure the file exists before it is changed
+	utils::read_content(&path)?;
+
+	let _lock = storage::lock(&path)?;
+	let before = storage::read(&path)?.unwrap_or_default();
+	let content = utils::set_value(&before, &key, value)?;
+	let config = utils::parse_config(&content).map_err(|err| match utils::parse_config(&before) {
+		Ok(_) => anyhow::format_err!("'{}' is not a valid value for {}: {}", value, key, err),
+		Err(_) => anyhow::format_err!("{}: {}, fix it with 'hey config edit'", path.display(), err)
+	})?;
+	// The config is only changed once the data is moved, a config that was broken has no data to move
+	if let Ok(before) = utils::parse_config(&before) {
+		migrate_storage(before, &config)?;
+	}
+	storage::write(&path, &content)?;
+
+	println!("⚙️ {} {}", format!("Set {} to", key).green(), utils::get_value(&config, &key)?.unwrap_or_default());
+	Ok(())
+}
+
+fn edit_command() -> anyhow::Result<()> {
+	// A config that is not valid can still be opened, that is how it gets fixed
+	let path = paths::get_config_path()?;
+	let before = utils::read_content(&path)?;
+	editor::edit_file(&path)?;
+
+	// Mistakes are reported right away instead of on the next command
+	let config = utils::read_config()?;
+	if let Ok(before) = utils::parse_config(&before) {
+		let format = before.storage_format;
+		if let Err(err) = migrate_storage(before, &config) {
+			// The data stays where it is, so the format it is stored in is put back
+			let _lock = storage::lock(&path)?;
+			let content = utils::set_value(&utils::read_content(&path)?, "storage_format", &format.to_string())?;
+			storage::write(&path, &content)?;
+			return Err(anyhow::format_err!("{}, storage_format was set back to {}", err, format));
+		}
+	}
+	println!("⚙️ {}", "Config saved".green());
+	Ok(())
+}
+
+// Moves the todos, reminders and notes to the new storage_format, see storage::migrate
+fn migrate_storage(before: Config, after: &Config) -> anyhow::Result<()> {
+	let (from, to) = (before.storage_format, after.storage_format);
+	if from == to {
+		return Ok(());
+	}
+
+	// The config isn't loaded for the config commands, the data directory can depend on it
+	let _ = CONFIG.set(before);
+	let moved = storage::migrate(from, to)?;
+	if !moved.is_empty() {
+		println!("📦 {}", format!("Moved {} from {} to {}", moved.join(", "), from, to).green());
+	}
+
+	Ok(())
+}
+
+fn check_key(key: &str) -> anyhow::Result<String> {
+	let keys = utils::get_keys();
+	let key = key.replace('-', "_");
+	if !keys.contains(&key) {
+		return Err(anyhow::format_err!("There is no config key '{}', the keys are: {}", key, keys.join(", ")));
+	}
+
+	Ok(key)
+}
+
+mod data;
+mod utils;
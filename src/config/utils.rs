@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use super::data::Config;
+use crate::{paths, storage};
+
+const DEFAULT_CONFIG: &str = include_str!("default.yaml");
+
+// Reads the config, the commented default config is written first if there is none yet
+pub fn read_config() -> anyhow::Result<Config> {
+	let path = paths::get_config_path()?;
+	let content = read_content(&path)?;
+	parse_config(&content).map_err(|err| anyhow::format_err!("{}: {}", path.display(), err))
+}
+
+// The text of the config without checking it, so a broken config can still be changed
+pub fn read_content(path: &Path) -> anyhow::Result<String> {
+	match storage::read(path)? {
+		Some(content) => Ok(content),
+		None => {
+			let _lock = storage::lock(path)?;
+			if storage::read(path)?.is_none() {
+				storage::write(path, DEFAULT_CONFIG)?;
+			}
+			Ok(DEFAULT_CONFIG.to_string())
+		}
+	}
+}
+
+pub fn parse_config(content: &str) -> anyhow::Result<Config> {
+	// An empty or fully commented file is read as null instead of an empty mapping
+	if content.lines().all(|line| line.trim().is_empty() || line.trim().starts_with('#')) {
+		return Ok(Config::default());
+	}
+
+	let config: Config = serde_yaml::from_str(content)?;
+	chrono::format::StrftimeItems::new(&config.date_format)
+		.parse()
+		.map_err(|_| anyhow::format_err!("'{}' is not a valid date_format", config.date_format))?;
+
+	Ok(config)
+}
+
+// The keys of the config as they are written in the file
+pub fn get_keys() -> Vec<String> {
+	match serde_yaml::to_value(Config{storage: Some(String::new()), notify_command: Some(String::new()), ..Config::default()}) {
+		Ok(serde_yaml::Value::Mapping(mapping)) => mapping.keys().filter_map(|key| key.as_str().map(|key| key.to_string())).collect(),
+		_ => Vec::new()
+	}
+}
+
+// The value of the key as YAML, None if the key is not set
+pub fn get_value(config: &Config, key: &str) -> anyhow::Result<Option<String>> {
+	let value = serde_yaml::to_value(config)?;
+	Ok(value.get(key).map(|value| match value {
+		serde_yaml::Value::String(text) => text.clone(),
+		value => serde_json::to_string(value).unwrap_or_default()
+	}))
+}
+
+/*
+	Sets the key in the text of the config, the comments and the order of the file are kept.
+	The line of the key is replaced, or the key is added at the end if the file doesn't have it yet.
+	Other lines with the same key are removed, a key that is given twice makes the config invalid.
+*/
+pub fn set_value(content: &str, key: &str, value: &str) -> anyhow::Result<String> {
+	// "true" stays a boolean while text like "%d/%m/%y" that isn't valid YAML on its own is quoted as a string
+	// A command like 'echo "a: b"' reads as a mapping, no key of the config is a mapping so it is kept as text
+	let value = match serde_yaml::from_str::<serde_yaml::Value>(value) {
+		Ok(parsed) if !parsed.is_mapping() => parsed,
+		_ => serde_yaml::Value::String(value.to_string())
+	};
+	// Text is written as it is when it reads back the same, everything else is written as JSON, which is YAML that fits on one line
+	let plain = serde_yaml::to_string(&value)?.trim().to_string();
+	let value = match serde_yaml::from_str::<serde_yaml::Value>(&plain) {
+		Ok(read_back) if read_back == value && value.is_string() && !plain.contains('\n') => plain,
+		_ => serde_json::to_string(&value)?
+	};
+	let line = format!("{}: {}", key, value);
+
+	let prefix = format!("{}:", key);
+	let mut lines: Vec<String> = Vec::new();
+	let mut found = false;
+	for existing in content.lines() {
+		match existing.starts_with(&prefix) {
+			true if found => (),
+			true => {
+				lines.push(line.clone());
+				found = true;
+			},
+			false => lines.push(existing.to_string())
+		}
+	}
+	if !found {
+		lines.push(line);
+	}
+
+	Ok(lines.join("\n") + "\n")
+}
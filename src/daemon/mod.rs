@@ -0,0 +1,81 @@
+/*
+	hey daemon keeps running and delivers reminders when they are due.
+
+	The reminder store is read again every interval, so reminders added while the daemon runs are picked up,
+	and the daemon wakes up early when a reminder is due before the next interval.
+	A reminder is marked as notified once a notifier delivered it. Reminders that became due while the daemon
+	wasn't running are still not notified, so they are delivered as missed when it starts again.
+*/
+
+use clap::Args;
+use colored::Colorize;
+
+use crate::{config::NotifierKind, remind};
+
+use notify::Notifier;
+
+// Reminders delivered later than this are shown as missed
+const MISSED_AFTER: chrono::TimeDelta = chrono::TimeDelta::minutes(1);
+
+#[derive(Args)]
+pub struct Command {
+	/// Deliver the reminders that are due and stop, e.g. to run it from cron
+	#[arg(long)]
+	once: bool,
+
+	/// Seconds between looking for new reminders
+	#[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+	interval: u64,
+
+	/// How reminders are delivered, can be given more than once. The notifiers in the config are used if not given
+	#[arg(short, long, value_enum)]
+	notifier: Vec<NotifierKind>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let notifiers = notify::get_notifiers(&command.notifier)?;
+	if command.once {
+		return deliver_due(&notifiers);
+	}
+
+	let names: Vec<&str> = notifiers.iter().map(|notifier| notifier.name()).collect();
+	println!("👀 {}", format!("Watching for reminders, delivered by {}", names.join(", ")).green());
+
+	loop {
+		// A store that can't be read, e.g. while it's edited by hand, is tried again on the next round
+		if let Err(err) = deliver_due(&notifiers) {
+			eprintln!("🚩 {}", err);
+		}
+
+		let interval = std::time::Duration::from_secs(command.interval);
+		let until_next = remind::get_next_due()
+			.ok()
+			.flatten()
+			.and_then(|due| (due - chrono::Utc::now()).to_std().ok());
+		std::thread::sleep(until_next.map_or(interval, |until_next| until_next.min(interval)));
+	}
+}
+
+fn deliver_due(notifiers: &[Box<dyn Notifier>]) -> anyhow::Result<()> {
+	let now = chrono::Utc::now();
+	for reminder in remind::get_due_reminders(now)? {
+		let missed = now - reminder.due > MISSED_AFTER;
+
+		// The reminder is only marked when at least one notifier delivered it, otherwise it's tried again
+		let mut delivered = false;
+		for notifier in notifiers {
+			match notifier.notify(&reminder, missed) {
+				Ok(()) => delivered = true,
+				Err(err) => eprintln!("🚩 The {} notifier could not deliver '{}': {}", notifier.name(), reminder.message, err)
+			}
+		}
+
+		if delivered {
+			remind::mark_notified(&reminder, now)?;
+		}
+	}
+
+	Ok(())
+}
+
+mod notify;
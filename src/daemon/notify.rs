@@ -0,0 +1,114 @@
+use colored::Colorize;
+
+use crate::{config::{self, NotifierKind}, remind::{self, Reminder}};
+
+// Delivers a reminder to the user. Missed reminders were due while the daemon wasn't running
+pub trait Notifier {
+	fn name(&self) -> &str;
+
+	fn notify(&self, reminder: &Reminder, missed: bool) -> anyhow::Result<()>;
+}
+
+// A notification on the desktop through the freedesktop notification service on D-Bus
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+	fn name(&self) -> &str {
+		"desktop"
+	}
+
+	fn notify(&self, reminder: &Reminder, missed: bool) -> anyhow::Result<()> {
+		let body = if missed {
+			format!("{}\nMissed, it was due {}", reminder.message, format_due(reminder))
+		} else {
+			reminder.message.clone()
+		};
+
+		notify_rust::Notification::new()
+			.appname("hey")
+			.summary("⏰ Reminder")
+			.body(&body)
+			.show()?;
+
+		Ok(())
+	}
+}
+
+// Prints the reminder and rings the bell of the terminal the daemon runs in
+pub struct TerminalNotifier;
+
+impl Notifier for TerminalNotifier {
+	fn name(&self) -> &str {
+		"terminal"
+	}
+
+	fn notify(&self, reminder: &Reminder, missed: bool) -> anyhow::Result<()> {
+		let missed = if missed { format!(" {}", "(missed)".red()) } else { String::new() };
+		println!("\x07⏰ {}{}", remind::format_reminder(reminder, true), missed);
+
+		Ok(())
+	}
+}
+
+// Runs the notify_command from the config with the reminder in $HEY_MESSAGE and $HEY_DUE, and $HEY_MISSED set to 1 for missed reminders
+pub struct CommandNotifier {
+	command: String
+}
+
+impl Notifier for CommandNotifier {
+	fn name(&self) -> &str {
+		"command"
+	}
+
+	fn notify(&self, reminder: &Reminder, missed: bool) -> anyhow::Result<()> {
+		let mut shell = if cfg!(windows) {
+			let mut shell = std::process::Command::new("cmd");
+			shell.arg("/C");
+			shell
+		} else {
+			let mut shell = std::process::Command::new("sh");
+			shell.arg("-c");
+			shell
+		};
+
+		let status = shell
+			.arg(&self.command)
+			.env("HEY_MESSAGE", &reminder.message)
+			.env("HEY_DUE", reminder.due.to_rfc3339())
+			.env("HEY_MISSED", if missed { "1" } else { "0" })
+			.status()?;
+
+		if !status.success() {
+			return Err(anyhow::format_err!("'{}' exited with {}", self.command, status));
+		}
+
+		Ok(())
+	}
+}
+
+// The notifiers from the config, or the kinds given instead
+pub fn get_notifiers(kinds: &[NotifierKind]) -> anyhow::Result<Vec<Box<dyn Notifier>>> {
+	let config = config::get();
+	let kinds = if kinds.is_empty() { &config.notifiers } else { kinds };
+	if kinds.is_empty() {
+		return Err(anyhow::format_err!("No notifiers are set, set them with 'hey config set notifiers [desktop, terminal]'"));
+	}
+
+	kinds
+		.iter()
+		.map(|kind| -> anyhow::Result<Box<dyn Notifier>> {
+			match kind {
+				NotifierKind::Desktop => Ok(Box::new(DesktopNotifier)),
+				NotifierKind::Terminal => Ok(Box::new(TerminalNotifier)),
+				NotifierKind::Command => {
+					let command = config.notify_command.clone().ok_or(anyhow::format_err!("The command notifier needs a command, set it with 'hey config set notify_command COMMAND'"))?;
+					Ok(Box::new(CommandNotifier{command}))
+				}
+			}
+		})
+		.collect()
+}
+
+fn format_due(reminder: &Reminder) -> String {
+	reminder.due.with_timezone(&chrono::Local).format(&config::get().timestamp_format()).to_string()
+}
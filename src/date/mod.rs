@@ -0,0 +1,90 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::config;
+
+pub use resolve::{to_local, When, DEFAULT_HOUR};
+
+// Accepts "today", "yesterday", "18/10/24", "2024-10-18" and dates written in the configured date format.
+// In the en-US locale "10/18/24" is read with the month first
+pub fn parse_date(date: &str) -> anyhow::Result<NaiveDate> {
+	parse_date_on(date, chrono::Local::now().date_naive())
+}
+
+// Same as parse_date, with "today" being the given day
+pub fn parse_date_on(date: &str, today: NaiveDate) -> anyhow::Result<NaiveDate> {
+	let config = config::get();
+	let numeric_format = if config.is_month_first() { "%m/%d/%y" } else { "%d/%m/%y" };
+
+	match date.to_lowercase().as_str() {
+		"today" => Ok(today),
+		"yesterday" => Ok(today.pred_opt().unwrap()),
+		_ => [config.date_format.as_str(), numeric_format, "%Y-%m-%d"]
+			.iter()
+			.find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+			.ok_or(anyhow::format_err!("Could not read the date '{}', use for example {}, 2024-10-18 or today", date, today.format(numeric_format)))
+	}
+}
+
+// Whether the local date of the timestamp is within the optional bounds, both bounds are inclusive
+pub fn is_between(timestamp: &chrono::DateTime<chrono::Utc>, from: Option<NaiveDate>, to: Option<NaiveDate>) -> bool {
+	let date = timestamp.with_timezone(&chrono::Local).date_naive();
+	from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+}
+
+// How far the timestamp is from now, like "in 3h 20m", "in 2d 4h" or "5m ago"
+pub fn format_relative(timestamp: chrono::DateTime<chrono::Utc>, now: chrono::DateTime<chrono::Utc>) -> String {
+	let delta = timestamp - now;
+	let total_minutes = delta.num_minutes().abs();
+	let (days, hours, minutes) = (total_minutes / (24 * 60), total_minutes / 60 % 24, total_minutes % 60);
+
+	let amount = match (days, hours, minutes) {
+		(0, 0, 0) => return "now".to_string(),
+		(0, 0, minutes) => format!("{}m", minutes),
+		(0, hours, 0) => format!("{}h", hours),
+		(0, hours, minutes) => format!("{}h {}m", hours, minutes),
+		(days, hours, _) if days < 7 && hours > 0 => format!("{}d {}h", days, hours),
+		(days, _, _) => format!("{}d", days)
+	};
+
+	match delta < chrono::TimeDelta::zero() {
+		true => format!("{} ago", amount),
+		false => format!("in {}", amount)
+	}
+}
+
+// A day in the future, like a due date. Accepts the same as parse_date, as well as "tomorrow" and weekdays like "friday"
+pub fn parse_day(day: &str) -> anyhow::Result<NaiveDate> {
+	parse_day_on(day, chrono::Local::now().date_naive())
+}
+
+// Same as parse_day, with "today" being the given day
+pub fn parse_day_on(day: &str, today: NaiveDate) -> anyhow::Result<NaiveDate> {
+	match day.to_lowercase().as_str() {
+		"tomorrow" => Ok(today.succ_opt().unwrap()),
+		word => match parse_weekday(word) {
+			Some(weekday) => Ok(next_weekday(today, weekday)),
+			None => parse_date_on(day, today)
+		}
+	}
+}
+
+// "on monday" is the coming monday, or today if today is a monday
+pub fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+	let days_ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+	today + chrono::Days::new(days_ahead as u64)
+}
+
+pub fn parse_weekday(word: &str) -> Option<Weekday> {
+	match word {
+		"monday" | "mon" => Some(Weekday::Mon),
+		"tuesday" | "tue" => Some(Weekday::Tue),
+		"wednesday" | "wed" => Some(Weekday::Wed),
+		"thursday" | "thu" => Some(Weekday::Thu),
+		"friday" | "fri" => Some(Weekday::Fri),
+		"saturday" | "sat" => Some(Weekday::Sat),
+		"sunday" | "sun" => Some(Weekday::Sun),
+		_ => None
+	}
+}
+
+mod resolve;
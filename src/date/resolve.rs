@@ -0,0 +1,386 @@
+/*
+	Resolves time clauses written in natural language, like "tomorrow at 5pm", "in two weeks" or "on monday", into a local time.
+	Everything is relative to the "now" the clauses are read with, the same words and now always give the same time.
+
+	Words that could mean more than one time are read as:
+		- A weekday is the next one to come, "on monday" on a monday is today if the time is still to come and next monday otherwise.
+		  "next monday" is never today
+		- A time without a day is the next time it comes, "at 9" after 9:00 is tomorrow
+		- A day without a time is at 9:00
+		- A time without am or pm uses the 24-hour clock, "at 5" is 5:00 in the morning
+		- A time with a relative day is on that day, "in two days at 5pm". Relative hours and minutes don't take a time
+		- "in a month" from the 31st is the last day of a shorter month
+		- A local time that doesn't exist because of daylight saving is an error, one that exists twice is the first of the two
+*/
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+
+use super::{next_weekday, parse_day_on, parse_weekday};
+use crate::{config, recurrence::{self, Recurrence}};
+
+// Time used when only a day is given, e.g. "on monday"
+pub const DEFAULT_HOUR: u32 = 9;
+
+// The time clauses read so far
+pub struct When {
+	now: DateTime<Local>,
+	day: Option<NaiveDate>,
+	// The day was given as a weekday, which moves a week ahead when its time has passed
+	weekday: bool,
+	time: Option<NaiveTime>,
+	offset: Option<Offset>,
+	recurrence: Option<Recurrence>
+}
+
+enum Offset {
+	Minutes(i64),
+	Days(u64),
+	Months(u32)
+}
+
+impl When {
+	pub fn new(now: DateTime<Local>) -> Self {
+		When {
+			now,
+			day: None,
+			weekday: false,
+			time: None,
+			offset: None,
+			recurrence: None
+		}
+	}
+
+	// Reads a phrase that only has time clauses, like "tomorrow at 5pm", "friday 10am" or "in ten minutes"
+	pub fn read_all<S: AsRef<str>>(words: &[S], now: DateTime<Local>) -> anyhow::Result<Self> {
+		let words: Vec<String> = words.iter().map(|word| word.as_ref().to_lowercase()).collect();
+		let mut when = When::new(now);
+		let mut idx = 0;
+		while idx < words.len() {
+			// A weekday or a time on its own, like "friday" or "5pm", can't be mistaken for content here
+			let implied = if parse_weekday(&words[idx]).is_some() { "on" } else { "at" };
+			let with_implied: Vec<&str> = std::iter::once(implied).chain(words[idx..].iter().map(|word| word.as_str())).collect();
+
+			idx += when.read_clause(&words[idx..])
+				.or_else(|| when.read_clause(&with_implied).map(|consumed| consumed - 1))
+				.ok_or_else(|| anyhow::format_err!("Did not understand '{}' as a time, try for example 'at 5pm', 'tomorrow' or 'in two hours'", words[idx]))?;
+		}
+
+		Ok(when)
+	}
+
+	pub fn recurrence(&self) -> Option<&Recurrence> {
+		self.recurrence.as_ref()
+	}
+
+	/*
+		Reads the time clause the words start with, returns the amount of words used or None if they don't start with one.
+		The words are expected in lowercase. Accepts "today", "tomorrow", "on monday", "next monday", "at 5pm", "at 5 pm",
+		"at 17:30", "in two weeks", "in half an hour", "next week", "every monday" and "until friday" after a recurrence.
+		A clause can only be given once, a second "at" is not read as a time.
+	*/
+	pub fn read_clause<S: AsRef<str>>(&mut self, words: &[S]) -> Option<usize> {
+		let words: Vec<&str> = words.iter().map(|word| word.as_ref()).collect();
+		let today = self.now.date_naive();
+		let first = *words.first()?;
+		let second = words.get(1).copied();
+		let third = words.get(2).copied();
+
+		match first {
+			"today" => self.set_day(today, false, 1),
+			"tomorrow" => self.set_day(today.succ_opt()?, false, 1),
+			"on" => {
+				let weekday = parse_weekday(second?)?;
+				self.set_day(next_weekday(today, weekday), true, 2)
+			},
+			"next" => match second? {
+				"week" => set_once(&mut self.offset, Offset::Days(7), 2),
+				"month" => set_once(&mut self.offset, Offset::Months(1), 2),
+				"year" => set_once(&mut self.offset, Offset::Months(12), 2),
+				word => {
+					let weekday = parse_weekday(word)?;
+					self.set_day(next_weekday(today.succ_opt()?, weekday), false, 2)
+				}
+			},
+			"at" => {
+				// Both "at 5pm" and "at 5 pm" are accepted
+				if let Some(meridiem @ ("am" | "pm")) = third {
+					let time = parse_time(&format!("{}{}", second?, meridiem))?;
+					set_once(&mut self.time, time, 3)
+				} else {
+					set_once(&mut self.time, parse_time(second?)?, 2)
+				}
+			},
+			"in" if second == Some("half") && matches!(third, Some("a" | "an")) && words.get(3).is_some_and(|word| word.trim_end_matches('s') == "hour") => {
+				set_once(&mut self.offset, Offset::Minutes(30), 4)
+			},
+			"in" => {
+				let count = parse_number(second?)?;
+				let offset = match third?.trim_end_matches('s') {
+					"minute" | "min" => Offset::Minutes(count as i64),
+					"hour" => Offset::Minutes(count as i64 * 60),
+					"day" => Offset::Days(count as u64),
+					"week" => Offset::Days(count as u64 * 7),
+					"fortnight" => Offset::Days(count as u64 * 14),
+					"month" => Offset::Months(count),
+					"year" => Offset::Months(count.checked_mul(12)?),
+					_ => return None
+				};
+				set_once(&mut self.offset, offset, 3)
+			},
+			"every" => {
+				let (recurrence, consumed) = recurrence::parse(&words, today)?;
+				set_once(&mut self.recurrence, recurrence, consumed)
+			},
+			// The end of the recurrence can also come after the other clauses, "every day at 5pm until friday"
+			"until" => {
+				let recurrence = self.recurrence.as_mut()?;
+				set_once(&mut recurrence.until, parse_day_on(second?, today).ok()?, 2)
+			},
+			_ => None
+		}
+	}
+
+	fn set_day(&mut self, day: NaiveDate, weekday: bool, consumed: usize) -> Option<usize> {
+		let consumed = set_once(&mut self.day, day, consumed)?;
+		self.weekday = weekday;
+		Some(consumed)
+	}
+
+	// The time the clauses describe, it has to be after now
+	pub fn resolve(self) -> anyhow::Result<DateTime<Local>> {
+		let now = self.now;
+
+		// "every monday at 9" starts at its first occurrence, a day that is given as well, like "tomorrow every week", is the first one
+		if let (Some(recurrence), None, None) = (&self.recurrence, self.day, &self.offset) {
+			return resolve_first(recurrence, self.time, now);
+		}
+
+		let default_time = NaiveTime::from_hms_opt(DEFAULT_HOUR, 0, 0).unwrap();
+		let due = match (self.day, self.time) {
+			(day, time) if self.offset.is_some() => {
+				if day.is_some() {
+					return Err(anyhow::format_err!("Both a day and a relative time was given, only one can be used"));
+				}
+				shift(now, self.offset.unwrap(), time)?
+			},
+			(Some(day), time) => {
+				let due = to_local(day, time.unwrap_or(default_time))?;
+				if self.weekday && due <= now {
+					to_local(day + chrono::Days::new(7), time.unwrap_or(default_time))?
+				} else {
+					due
+				}
+			},
+			(None, Some(time)) => {
+				let due = to_local(now.date_naive(), time)?;
+				if due <= now {
+					to_local(now.date_naive().succ_opt().unwrap(), time)?
+				} else {
+					due
+				}
+			},
+			(None, None) => return Err(anyhow::format_err!("No time given, try for example 'at 5pm', 'tomorrow' or 'in two hours'"))
+		};
+
+		if due <= now {
+			return Err(anyhow::format_err!("The time {} has already passed", due.format(&config::get().timestamp_format())));
+		}
+
+		Ok(due)
+	}
+}
+
+// Now moved by the offset, on that day at the time if one is given
+fn shift(now: DateTime<Local>, offset: Offset, time: Option<NaiveTime>) -> anyhow::Result<DateTime<Local>> {
+	let shifted = match offset {
+		Offset::Minutes(minutes) => now.checked_add_signed(chrono::Duration::minutes(minutes)),
+		Offset::Days(days) => now.checked_add_days(chrono::Days::new(days)),
+		Offset::Months(months) => now.checked_add_months(chrono::Months::new(months))
+	}.ok_or(anyhow::format_err!("The time is out of range"))?;
+
+	match (offset, time) {
+		(Offset::Minutes(_), Some(_)) => Err(anyhow::format_err!("A time of the day can't be given with a relative time in minutes or hours")),
+		(_, Some(time)) => to_local(shifted.date_naive(), time),
+		(_, None) => Ok(shifted)
+	}
+}
+
+// A clause can only be given once, a second "at" is treated as part of the content
+fn set_once<T>(field: &mut Option<T>, value: T, consumed: usize) -> Option<usize> {
+	if field.is_some() {
+		return None;
+	}
+
+	*field = Some(value);
+	Some(consumed)
+}
+
+// The first occurrence that is still to come, on a monday afternoon "every monday at 9" starts next monday
+fn resolve_first(recurrence: &Recurrence, time: Option<NaiveTime>, now: DateTime<Local>) -> anyhow::Result<DateTime<Local>> {
+	let time = time.unwrap_or(NaiveTime::from_hms_opt(DEFAULT_HOUR, 0, 0).unwrap());
+	let ended = || anyhow::format_err!("The recurrence ends before its first time");
+
+	let mut day = recurrence.first_on_or_after(now.date_naive()).ok_or_else(ended)?;
+	loop {
+		let due = to_local(day, time)?;
+		if due > now {
+			return Ok(due);
+		}
+		day = recurrence.next_after(day).ok_or_else(ended)?;
+	}
+}
+
+pub fn to_local(day: NaiveDate, time: NaiveTime) -> anyhow::Result<DateTime<Local>> {
+	Local.from_local_datetime(&day.and_time(time))
+		.earliest()
+		.ok_or(anyhow::format_err!("The time {} does not exist in the local timezone", day.and_time(time)))
+}
+
+// Accepts "5pm", "5:30pm", "17:30", "17", "noon" and "midnight"
+fn parse_time(word: &str) -> Option<NaiveTime> {
+	match word {
+		"noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+		"midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+		_ => ()
+	}
+
+	let (clock, meridiem) = if let Some(clock) = word.strip_suffix("am") {
+		(clock, Some(false))
+	} else if let Some(clock) = word.strip_suffix("pm") {
+		(clock, Some(true))
+	} else {
+		(word, None)
+	};
+
+	let (hour, minute) = match clock.split_once(':') {
+		Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+		None => (clock.parse::<u32>().ok()?, 0)
+	};
+
+	let hour = match meridiem {
+		Some(_) if hour == 0 || hour > 12 => return None,
+		Some(false) => hour % 12,
+		Some(true) => hour % 12 + 12,
+		None => hour
+	};
+
+	NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn parse_number(word: &str) -> Option<u32> {
+	let number = match word {
+		"a" | "an" | "one" => 1,
+		"two" => 2,
+		"three" => 3,
+		"four" => 4,
+		"five" => 5,
+		"six" => 6,
+		"seven" => 7,
+		"eight" => 8,
+		"nine" => 9,
+		"ten" => 10,
+		"eleven" => 11,
+		"twelve" => 12,
+		"fifteen" => 15,
+		"twenty" => 20,
+		"thirty" => 30,
+		"forty" => 40,
+		"fifty" => 50,
+		_ => return word.parse().ok()
+	};
+
+	Some(number)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Monday 6 January 2025, far from any daylight saving change
+	fn monday_at(hour: u32, minute: u32) -> DateTime<Local> {
+		at(2025, 1, 6, hour, minute)
+	}
+
+	fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+		to_local(NaiveDate::from_ymd_opt(year, month, day).unwrap(), NaiveTime::from_hms_opt(hour, minute, 0).unwrap()).unwrap()
+	}
+
+	fn resolve(phrase: &str, now: DateTime<Local>) -> anyhow::Result<DateTime<Local>> {
+		let words: Vec<&str> = phrase.split_whitespace().collect();
+		When::read_all(&words, now)?.resolve()
+	}
+
+	#[test]
+	fn weekday_on_the_same_day_is_today_while_the_time_is_to_come() {
+		assert_eq!(resolve("on monday", monday_at(8, 0)).unwrap(), monday_at(9, 0));
+		assert_eq!(resolve("on monday at 5pm", monday_at(12, 0)).unwrap(), monday_at(17, 0));
+	}
+
+	#[test]
+	fn weekday_on_the_same_day_is_next_week_once_the_time_passed() {
+		assert_eq!(resolve("on monday", monday_at(10, 0)).unwrap(), at(2025, 1, 13, 9, 0));
+		assert_eq!(resolve("monday", monday_at(10, 0)).unwrap(), at(2025, 1, 13, 9, 0));
+	}
+
+	#[test]
+	fn next_weekday_is_never_today() {
+		assert_eq!(resolve("next monday", monday_at(8, 0)).unwrap(), at(2025, 1, 13, 9, 0));
+		assert_eq!(resolve("next tuesday", monday_at(8, 0)).unwrap(), at(2025, 1, 7, 9, 0));
+	}
+
+	#[test]
+	fn time_without_a_day_is_the_next_time_it_comes() {
+		assert_eq!(resolve("at 9", monday_at(8, 0)).unwrap(), monday_at(9, 0));
+		assert_eq!(resolve("at 9", monday_at(9, 30)).unwrap(), at(2025, 1, 7, 9, 0));
+		assert_eq!(resolve("5pm", monday_at(9, 30)).unwrap(), monday_at(17, 0));
+	}
+
+	#[test]
+	fn time_without_meridiem_uses_the_24_hour_clock() {
+		assert_eq!(resolve("at 5", monday_at(1, 0)).unwrap(), monday_at(5, 0));
+		assert_eq!(resolve("at 17:30", monday_at(1, 0)).unwrap(), monday_at(17, 30));
+		assert_eq!(resolve("at 5 pm", monday_at(1, 0)).unwrap(), monday_at(17, 0));
+	}
+
+	#[test]
+	fn day_without_a_time_is_at_the_default_hour() {
+		assert_eq!(resolve("tomorrow", monday_at(20, 0)).unwrap(), at(2025, 1, 7, DEFAULT_HOUR, 0));
+	}
+
+	#[test]
+	fn relative_day_takes_a_time() {
+		assert_eq!(resolve("in two days at 5pm", monday_at(20, 0)).unwrap(), at(2025, 1, 8, 17, 0));
+		assert_eq!(resolve("in half an hour", monday_at(20, 0)).unwrap(), monday_at(20, 30));
+		assert!(resolve("in two hours at 5pm", monday_at(8, 0)).is_err());
+	}
+
+	#[test]
+	fn month_from_the_31st_ends_on_the_last_day_of_a_shorter_month() {
+		assert_eq!(resolve("in a month", at(2025, 1, 31, 10, 0)).unwrap(), at(2025, 2, 28, 10, 0));
+		assert_eq!(resolve("in a month", at(2024, 1, 31, 10, 0)).unwrap(), at(2024, 2, 29, 10, 0));
+	}
+
+	#[test]
+	fn passed_time_is_an_error() {
+		assert!(resolve("today at 8", monday_at(10, 0)).is_err());
+		assert!(resolve("in 0 minutes", monday_at(10, 0)).is_err());
+		assert!(resolve("in 0 days at 8", monday_at(10, 0)).is_err());
+	}
+
+	#[test]
+	fn recurrence_starts_at_its_first_time_to_come() {
+		assert_eq!(resolve("every monday at 9", monday_at(8, 0)).unwrap(), monday_at(9, 0));
+		assert_eq!(resolve("every monday at 9", monday_at(10, 0)).unwrap(), at(2025, 1, 13, 9, 0));
+	}
+
+	#[test]
+	fn recurrence_end_is_read_from_now() {
+		let words = ["every", "day", "until", "friday"];
+		let when = When::read_all(&words, monday_at(8, 0)).unwrap();
+		assert_eq!(when.recurrence().unwrap().until, NaiveDate::from_ymd_opt(2025, 1, 10));
+
+		let words = ["every", "day", "at", "5pm", "until", "friday"];
+		let when = When::read_all(&words, at(2025, 1, 8, 8, 0)).unwrap();
+		assert_eq!(when.recurrence().unwrap().until, NaiveDate::from_ymd_opt(2025, 1, 10));
+	}
+}
@@ -0,0 +1,21 @@
+// Opens the file in $VISUAL or $EDITOR, or vi if neither is set, and waits until the editor is closed
+pub fn edit_file(path: &std::path::Path) -> anyhow::Result<()> {
+	let editor = std::env::var("VISUAL")
+		.or_else(|_| std::env::var("EDITOR"))
+		.unwrap_or("vi".to_string());
+
+	// The editor can include arguments, e.g. "code --wait"
+	let mut words = editor.split_whitespace();
+	let program = words.next().ok_or(anyhow::format_err!("$EDITOR is empty"))?;
+	let status = std::process::Command::new(program)
+		.args(words)
+		.arg(path)
+		.status()
+		.map_err(|err| anyhow::format_err!("Could not start the editor '{}': {}", editor, err))?;
+
+	if !status.success() {
+		return Err(anyhow::format_err!("The editor '{}' exited with an error", editor));
+	}
+
+	Ok(())
+}
@@ -0,0 +1,189 @@
+/*
+	Turns free text, "hey -- remind me to X at Y", into the arguments of the matching subcommand, "hey remind me to X at Y".
+	The text is tokenized, parsed with the sentence grammar and the node below the start rule decides the subcommand.
+*/
+
+use crate::{grammar, parser::{Node, Parser, Symbol}, todo, tokenizer::{Token, TokenDefinition, Tokenizer}};
+
+const EXAMPLES: [&str; 10] = [
+	"hey -- remind me to water the plants at 5pm",
+	"hey -- every monday at 9 remind me to take out the bins",
+	"hey -- snooze that for an hour",
+	"hey -- note down that it is Tuesday",
+	"hey -- create a list called tool ideas",
+	"hey -- add to list tool ideas write a new cli",
+	"hey -- add buy milk to todo",
+	"hey -- show me all lists",
+	"hey -- show me all lists where cli is",
+	"hey -- check todo"
+];
+
+pub fn to_command_args(free_text: &[String]) -> anyhow::Result<Vec<String>> {
+	let grammar = grammar::load()?;
+	let mut token_definitions = grammar.token_definitions;
+	let list_names: Vec<String> = todo::get_list_names()?.iter().map(|name| name.to_lowercase()).collect();
+	token_definitions.push(TokenDefinition::new("LIST_NAME", list_names.iter().map(|name| name.as_str()).collect()));
+
+	let tokenizer = Tokenizer::new(token_definitions);
+	let parser = Parser::new(grammar.rules)?;
+
+	let sentence = free_text.join(" ");
+	let tree = tokenizer
+		.tokenize(&sentence)
+		.and_then(|tokens| parser.parse(tokens))
+		.ok_or_else(|| not_understood(&sentence))?;
+
+	let command = match tree.children.first() {
+		Some(Symbol::NonTerminal(node)) => node,
+		_ => return Err(not_understood(&sentence))
+	};
+
+	let args = match command.name.as_str() {
+		"REMIND_SENTENCE" => get_remind_args(command),
+		"ADD_SENTENCE" => get_add_args(command),
+		"SHOW_SENTENCE" if command.token("WHERE").is_some() => get_where_args(command),
+		"SHOW_SENTENCE" if command.token("NOTES").is_some() => to_args(&["note", "list"]),
+		"SHOW_SENTENCE" if command.token("REMINDER").is_some() => to_args(&["remind", "list"]),
+		"SHOW_SENTENCE" if command.child("TARGET").is_none() => to_args(&["todo", "show", "--lists"]),
+		"SHOW_SENTENCE" => with_list_arg(&["todo", "show"], None, command),
+		"CHECK_SENTENCE" => with_list_arg(&["todo", "check"], Some("--list"), command),
+		"CREATE_SENTENCE" => get_text_args(&["todo", "create"], command, "CONTENT"),
+		"NOTE_SENTENCE" => get_text_args(&["note", "add"], command, "CONTENT"),
+		"DELETE_SENTENCE" => with_list_arg(&["todo", "delete"], None, command),
+		"SNOOZE_SENTENCE" => get_reminder_args(&["remind", "snooze"], command),
+		"CANCEL_SENTENCE" => get_reminder_args(&["remind", "cancel"], command),
+		"RESCHEDULE_SENTENCE" => get_reminder_args(&["remind", "reschedule"], command),
+		"RENAME_SENTENCE" => {
+			let new_name = get_text(command, "TEXT").join(" ");
+			with_list_arg(&["todo", "rename", &format!("--to={}", new_name)], None, command)
+		},
+		_ => return Err(not_understood(&sentence))
+	};
+
+	Ok(args)
+}
+
+/*
+	The words of the user are only ever passed after "--" or as the value of "--flag=value", so content like "-5" or "--list"
+	is never read as an option of the subcommand.
+*/
+
+// The list name from the TARGET of a sentence, None for the default list
+fn get_list_name(command: &Node) -> Option<String> {
+	let list_ref = command.child("TARGET")?.child("LIST_REF")?;
+	list_ref.token("LIST_NAME").map(|token| token.value.clone())
+}
+
+// The list is added as an option if a flag is given, otherwise as a positional argument
+fn with_list_arg(args: &[&str], flag: Option<&str>, command: &Node) -> Vec<String> {
+	let mut args = to_args(args);
+	let list = get_list_name(command).unwrap_or_else(todo::default_list);
+	match flag {
+		Some(flag) => args.push(format!("{}={}", flag, list)),
+		None => args.extend(["--".to_string(), list])
+	}
+	args
+}
+
+// "show me all lists where X is", the trailing "is" is not part of what is searched for
+fn get_where_args(command: &Node) -> Vec<String> {
+	let mut text = get_text(command, "TEXT");
+	if text.len() > 1 && ["is", "are", "exists"].contains(&text.last().unwrap().to_lowercase().as_str()) {
+		text.pop();
+	}
+
+	to_args(&["todo", "show", &format!("--where={}", text.join(" "))])
+}
+
+fn get_text_args(args: &[&str], command: &Node, text: &str) -> Vec<String> {
+	let mut args = to_args(args);
+	args.push("--".to_string());
+	args.extend(get_text(command, text));
+	args
+}
+
+fn get_text(command: &Node, text: &str) -> Vec<String> {
+	command.child(text).map_or(Vec::new(), |text| text.values().iter().map(|value| value.to_string()).collect())
+}
+
+fn to_args(args: &[&str]) -> Vec<String> {
+	args.iter().map(|arg| arg.to_string()).collect()
+}
+
+// The words in front of the command are moved last, "at Y remind me to X" -> "remind me to X at Y"
+fn get_remind_args(command: &Node) -> Vec<String> {
+	let prefix: Vec<&str> = command.children
+		.iter()
+		.take_while(|child| !matches!(child, Symbol::Terminal(token) if token.name == "REMIND"))
+		.flat_map(|child| match child {
+			Symbol::NonTerminal(node) => node.values(),
+			Symbol::Terminal(token) => vec![token.value.as_str()]
+		})
+		.collect();
+	let subject = command.token("SUBJECT").map_or("me", |token| token.value.as_str());
+
+	let mut args = vec!["remind", "--", subject];
+	args.extend(command.child("TEXT").map(|text| text.values()).unwrap_or_default());
+	args.extend(prefix);
+	to_args(&args)
+}
+
+// The reminder id followed by the time, "that" and "it" are the reminder that was delivered last
+fn get_reminder_args(args: &[&str], command: &Node) -> Vec<String> {
+	let mut args = to_args(args);
+	let id = command.child("REMINDER_REF").and_then(|reference| reference.token("WORD")).map_or("last", |token| token.value.as_str());
+	args.extend(["--".to_string(), if id.eq_ignore_ascii_case("it") { "last" } else { id }.to_string()]);
+	args.extend(get_text(command, "TEXT"));
+	args
+}
+
+fn get_add_args(command: &Node) -> Vec<String> {
+	match command.child("TARGET") {
+		Some(_) => {
+			let list_args = with_list_arg(&["todo", "add"], Some("--list"), command);
+			let list_args: Vec<&str> = list_args.iter().map(|arg| arg.as_str()).collect();
+			get_text_args(&list_args, command, "TEXT")
+		},
+		None => {
+			let tokens = command.child("CONTENT").map(|content| content.tokens()).unwrap_or_default();
+			let (content, list) = split_trailing_list(&tokens);
+			let mut args = to_args(&["todo", "add"]);
+			args.extend(list.map(|list| format!("--list={}", list)));
+			args.push("--".to_string());
+			args.extend(content.iter().map(|token| token.value.clone()));
+			args
+		}
+	}
+}
+
+/*
+	"add buy milk to todo" and "add write docs to my list tool ideas" end in the list to add to. A rule for it would conflict
+	with "add CONTENT", which can have "to todo" at its end as well, so the list is split off the content here instead.
+	The content is everything in front of the last "to" that only has a list after it.
+*/
+fn split_trailing_list<'a>(tokens: &[&'a Token]) -> (Vec<&'a Token>, Option<String>) {
+	let is = |token: &Token, names: &[&str]| names.contains(&token.name.as_str());
+	let list_at = |idx: usize| -> Option<Option<String>> {
+		let rest: Vec<&Token> = tokens[idx..].iter().copied().skip_while(|token| is(token, &["SUBJECT", "FILLER"])).collect();
+		match rest.as_slice() {
+			[todo] if is(todo, &["TODO"]) => Some(None),
+			[name] if is(name, &["LIST_NAME"]) => Some(Some(name.value.clone())),
+			[list, name] if is(list, &["LIST"]) && is(name, &["LIST_NAME"]) => Some(Some(name.value.clone())),
+			_ => None
+		}
+	};
+
+	let split = (1..tokens.len()).rev().find_map(|idx| match is(tokens[idx], &["TO"]) {
+		true => list_at(idx + 1).map(|list| (idx, list)),
+		false => None
+	});
+
+	match split {
+		Some((idx, list)) => (tokens[..idx].to_vec(), list),
+		None => (tokens.to_vec(), None)
+	}
+}
+
+fn not_understood(sentence: &str) -> anyhow::Error {
+	anyhow::format_err!("Did not understand \"{}\", try for example:\n  {}", sentence, EXAMPLES.join("\n  "))
+}
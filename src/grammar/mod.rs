@@ -0,0 +1,118 @@
+/*
+	Loads the grammar used for free text. The built-in grammar is always loaded, and ~/.hey/grammar extends it if it exists.
+	Both are written in the same format, see default.grammar for a description of it.
+
+	Tokens from the user grammar are matched before the built-in ones, and user rules are added after the built-in rules.
+	A new phrasing like "ping me to X" therefore only needs "synonyms remind: ping" in ~/.hey/grammar.
+*/
+
+use crate::{parser::Rule, paths, thesaurus::{self, Thesaurus}, tokenizer::TokenDefinition};
+
+const DEFAULT_GRAMMAR: &str = include_str!("default.grammar");
+
+pub struct Grammar {
+	pub token_definitions: Vec<TokenDefinition>,
+	pub rules: Vec<Rule>
+}
+
+enum Pattern {
+	Words(Vec<String>),
+	Regex(regex::Regex)
+}
+
+#[derive(Default)]
+struct GrammarFile {
+	tokens: Vec<(String, Pattern)>,
+	rules: Vec<(String, String)>,
+	synonyms: Vec<(String, Vec<String>)>
+}
+
+pub fn load() -> anyhow::Result<Grammar> {
+	let mut grammar = read(DEFAULT_GRAMMAR, "built-in grammar")?;
+
+	let path = paths::get_grammar_path()?;
+	if path.exists() {
+		let mut user_grammar = read(&std::fs::read_to_string(&path)?, &path.display().to_string())?;
+		user_grammar.tokens.append(&mut grammar.tokens);
+		grammar.tokens = user_grammar.tokens;
+		grammar.rules.append(&mut user_grammar.rules);
+		grammar.synonyms.append(&mut user_grammar.synonyms);
+	}
+
+	Ok(build(grammar))
+}
+
+fn build(grammar: GrammarFile) -> Grammar {
+	let mut thesaurus = Thesaurus::new();
+	thesaurus::add_default_synonyms(&mut thesaurus);
+	for (word, synonyms) in &grammar.synonyms {
+		thesaurus.add_synonyms(word, synonyms.iter().map(|s| s.as_str()).collect());
+	}
+
+	let token_definitions = grammar.tokens
+		.into_iter()
+		.map(|(name, pattern)| match pattern {
+			Pattern::Regex(regex) => TokenDefinition::new_with_regex(&name, regex),
+			Pattern::Words(words) => {
+				// Every word also matches its synonyms, "add" matches "put" as well
+				let mut all_words: Vec<&str> = Vec::new();
+				for word in &words {
+					all_words.push(word);
+					all_words.extend(thesaurus.get_synonyms(word).iter().map(|s| s.as_str()));
+				}
+				TokenDefinition::new(&name, all_words)
+			}
+		})
+		.collect();
+
+	let rules = grammar.rules
+		.into_iter()
+		.map(|(name, rule)| Rule::new(name, rule))
+		.collect();
+
+	Grammar {
+		token_definitions,
+		rules
+	}
+}
+
+fn read(content: &str, source: &str) -> anyhow::Result<GrammarFile> {
+	let mut grammar = GrammarFile::default();
+
+	for (idx, line) in content.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		let error = |message: &str| anyhow::format_err!("{}, line {}: {}\n  {}", source, idx + 1, message, line);
+		let (keyword, definition) = line.split_once(' ').ok_or_else(|| error("Expected 'token', 'rule' or 'synonyms' followed by a definition"))?;
+
+		match keyword {
+			"token" => {
+				let (name, pattern) = definition.split_once(':').ok_or_else(|| error("Expected 'token NAME: words' or 'token NAME: /regex/'"))?;
+				let pattern = pattern.trim();
+				let pattern = if let Some(regex) = pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+					Pattern::Regex(regex::Regex::new(regex).map_err(|e| error(&e.to_string()))?)
+				} else if !pattern.is_empty() {
+					Pattern::Words(pattern.split_whitespace().map(|w| w.to_lowercase()).collect())
+				} else {
+					return Err(error("A token needs at least one word or a regex"));
+				};
+				grammar.tokens.push((name.trim().to_string(), pattern));
+			},
+			"rule" => {
+				let (name, rule) = definition.split_once("->").ok_or_else(|| error("Expected 'rule NAME -> SYMBOLS'"))?;
+				grammar.rules.push((name.trim().to_string(), rule.trim().to_string()));
+			},
+			"synonyms" => {
+				let (word, synonyms) = definition.split_once(':').ok_or_else(|| error("Expected 'synonyms word: synonym, synonym'"))?;
+				let synonyms = synonyms.split(',').map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()).collect();
+				grammar.synonyms.push((word.trim().to_lowercase(), synonyms));
+			},
+			_ => return Err(error(&format!("Unknown keyword '{}'", keyword)))
+		}
+	}
+
+	Ok(grammar)
+}
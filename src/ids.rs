@@ -0,0 +1,37 @@
+/*
+	Ids of the todos and reminders. Each storage keeps the id it gives next, so an id is never reused after its item is removed.
+	It is written as a plain number, "next_id: 12".
+*/
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy)]
+#[serde(transparent)]
+pub struct NextId(u64);
+
+impl Default for NextId {
+	fn default() -> Self {
+		NextId(1)
+	}
+}
+
+impl NextId {
+	pub fn take(&mut self) -> u64 {
+		let id = self.0;
+		self.0 += 1;
+		id
+	}
+
+	// Items from before ids existed have the id 0 and are given one, returns true if any id was given
+	pub fn migrate<'a>(&mut self, ids: impl IntoIterator<Item = &'a mut u64>) -> bool {
+		let mut ids: Vec<&mut u64> = ids.into_iter().collect();
+		let highest_id = ids.iter().map(|id| **id).max().unwrap_or(0);
+		self.0 = self.0.max(highest_id + 1);
+
+		let mut migrated = false;
+		for id in ids.iter_mut().filter(|id| ***id == 0) {
+			**id = self.take();
+			migrated = true;
+		}
+
+		migrated
+	}
+}
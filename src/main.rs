@@ -1,6 +1,22 @@
 mod remind;
 mod todo;
+mod note;
+mod config;
+mod daemon;
+mod date;
+mod editor;
+mod ids;
 mod paths;
+mod picker;
+mod recurrence;
+mod search;
+mod storage;
+mod free_text;
+mod grammar;
+mod parse_table;
+mod parser;
+mod thesaurus;
+mod tokenizer;
 
 use clap::{Parser, Subcommand};
 use colored::Colorize;
@@ -12,6 +28,10 @@ struct Cli {
 	#[command(subcommand)]
 	command: Option<Commands>,
 
+	/// Directory of the todos, notes and reminders, overrides HEY_HOME and the storage in the config
+	#[arg(long, global = true, value_name = "DIR")]
+	data_dir: Option<std::path::PathBuf>,
+
 	#[arg(last = true)]
 	free_text: Vec<String>
 }
@@ -33,7 +53,11 @@ All commands should be available as synonyms as well by calling "hey -- "
 #[derive(Subcommand)]
 enum Commands {
 	Remind(remind::Command),
-	Todo(todo::Command)
+	Todo(todo::Command),
+	Note(note::Command),
+	Config(config::Command),
+	/// Keep running and deliver reminders when they are due
+	Daemon(daemon::Command)
 }
 
 fn main() {
@@ -47,20 +71,36 @@ fn main() {
 
 fn run() -> anyhow::Result<()> {
 	let cli = Cli::parse();
+	if let Some(data_dir) = cli.data_dir {
+		paths::set_data_dir(data_dir);
+	}
+	// The config commands read the config themselves, so a broken config can still be fixed with them
+	if !matches!(cli.command, Some(Commands::Config(_))) {
+		config::load()?;
+	}
 
-	if let Some(commands) = cli.command {
-		match commands {
-			Commands::Remind(input) => remind::execute(input),
-			Commands::Todo(input) => todo::execute(input)
-		}
+	if let Some(command) = cli.command {
+		execute(command)
 	} else if !cli.free_text.is_empty() {
-		println!("Not implemented yet. Text inputed: {}", cli.free_text.join(" "));
-		Ok(())
+		// Free text is translated to the arguments of a subcommand and parsed again, so both take the same path
+		let args = free_text::to_command_args(&cli.free_text)?;
+		let cli = Cli::try_parse_from(std::iter::once("hey".to_string()).chain(args))?;
+		cli.command.map_or(Ok(()), execute)
 	} else {
 		Ok(())
 	}
 }
 
+fn execute(command: Commands) -> anyhow::Result<()> {
+	match command {
+		Commands::Remind(input) => remind::execute(input),
+		Commands::Todo(input) => todo::execute(input),
+		Commands::Note(input) => note::execute(input),
+		Commands::Config(input) => config::execute(input),
+		Commands::Daemon(input) => daemon::execute(input)
+	}
+}
+
 // hey todo add
 // hey todo show
 // hey todo check
\ No newline at end of file
@@ -0,0 +1,21 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::{data::Note, utils};
+
+#[derive(Args)]
+pub struct Command {
+	#[arg(required = true)]
+	text: Vec<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	utils::update_notes(|notes| {
+		notes.push(Note{timestamp: chrono::Utc::now(), text: command.text.join(" ")});
+		Ok(())
+	})?;
+
+	println!("🗒️ {}", "Note added!".green());
+
+	Ok(())
+}
@@ -0,0 +1,10 @@
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+pub struct NoteStorage {
+	pub notes: Vec<Note>
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Note {
+	pub timestamp: chrono::DateTime<chrono::Utc>,
+	pub text: String
+}
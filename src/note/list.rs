@@ -0,0 +1,36 @@
+use clap::Args;
+
+use super::utils;
+use crate::date;
+
+#[derive(Args)]
+pub struct Command {
+	/// Only show notes written on or after this date, e.g. 18/10/24, 2024-10-18 or yesterday
+	#[arg(long)]
+	from: Option<String>,
+
+	/// Only show notes written on or before this date
+	#[arg(long)]
+	to: Option<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let from = command.from.as_deref().map(date::parse_date).transpose()?;
+	let to = command.to.as_deref().map(date::parse_date).transpose()?;
+
+	let notes = utils::get_notes()?;
+	let notes: Vec<_> = notes
+		.iter()
+		.filter(|note| date::is_between(&note.timestamp, from, to))
+		.collect();
+
+	if notes.is_empty() {
+		println!("📭 No notes found");
+	}
+
+	for note in notes {
+		println!("{}", utils::format_note(note, true));
+	}
+
+	Ok(())
+}
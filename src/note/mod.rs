@@ -0,0 +1,38 @@
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct Command {
+	#[command(subcommand)]
+	command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+	Add(add::Command),
+	List(list::Command),
+	Search(search::Command)
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	match command.command {
+		Commands::Add(input) => add::execute(input),
+		Commands::List(input) => list::execute(input),
+		Commands::Search(input) => search::execute(input),
+	}
+}
+
+// Notes containing the query, formatted with the matches highlighted
+pub fn find_notes(query: &str) -> anyhow::Result<Vec<String>> {
+	let words = vec![query.to_string()];
+	Ok(utils::get_notes()?
+		.iter()
+		.filter(|note| crate::search::contains(&note.text, query))
+		.map(|note| utils::format_note_text(note, &crate::search::highlight(&note.text, &words), true))
+		.collect())
+}
+
+mod add;
+mod list;
+mod search;
+mod data;
+mod utils;
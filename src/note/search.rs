@@ -0,0 +1,31 @@
+use clap::Args;
+
+use super::utils;
+use crate::search;
+
+#[derive(Args)]
+pub struct Command {
+	/// Words that all have to be in the note, without regard to case
+	#[arg(required = true)]
+	query: Vec<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let query: Vec<String> = command.query.iter().map(|word| word.to_lowercase()).collect();
+
+	let notes = utils::get_notes()?;
+	let matches: Vec<_> = notes
+		.iter()
+		.filter(|note| query.iter().all(|word| search::contains(&note.text, word)))
+		.collect();
+
+	if matches.is_empty() {
+		println!("📭 No notes matching '{}'", command.query.join(" "));
+	}
+
+	for note in matches {
+		println!("{}", utils::format_note_text(note, &search::highlight(&note.text, &query), true));
+	}
+
+	Ok(())
+}
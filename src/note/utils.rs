@@ -0,0 +1,29 @@
+use colored::Colorize;
+
+use super::data::{Note, NoteStorage};
+use crate::{config, storage};
+
+pub fn get_notes() -> anyhow::Result<Vec<Note>> {
+	let data: NoteStorage = storage::load("note")?;
+
+	Ok(data.notes)
+}
+
+// Changes the notes as one transaction
+pub fn update_notes<R>(change: impl FnOnce(&mut Vec<Note>) -> anyhow::Result<R>) -> anyhow::Result<R> {
+	storage::update("note", |data: &mut NoteStorage| change(&mut data.notes))
+}
+
+pub fn format_note(note: &Note, colors: bool) -> String {
+	format_note_text(note, &note.text, colors)
+}
+
+// Same as format_note, but with a text that has been changed, e.g. with highlighted search matches
+pub fn format_note_text(note: &Note, text: &str, colors: bool) -> String {
+	let timestamp = note.timestamp.with_timezone(&chrono::Local).format(&config::get().timestamp_format()).to_string();
+	if colors {
+		format!("{} {}", timestamp.cyan(), text)
+	} else {
+		format!("{} {}", timestamp, text)
+	}
+}
@@ -1,17 +1,73 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Debug)]
 pub enum ParseTableError {
 	NoRulesSpecified,
 	InvalidTableRule,
 	InvalidSymbolForSet,
-	InvalidSetIndex
+	InvalidSetIndex,
+	Conflicts(Vec<Conflict>)
+}
+
+impl std::fmt::Display for ParseTableError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ParseTableError::NoRulesSpecified => write!(f, "No rules were specified for the grammar"),
+			ParseTableError::InvalidTableRule => write!(f, "A rule in the grammar is invalid"),
+			ParseTableError::InvalidSymbolForSet => write!(f, "The symbol is not expected in the set"),
+			ParseTableError::InvalidSetIndex => write!(f, "The set does not exist"),
+			ParseTableError::Conflicts(conflicts) => {
+				write!(f, "The grammar is ambiguous, found {} conflict(s):", conflicts.len())?;
+				for conflict in conflicts {
+					write!(f, "\n{}", conflict)?;
+				}
+				Ok(())
+			}
+		}
+	}
+}
+
+impl std::error::Error for ParseTableError {}
+
+#[derive(Debug, PartialEq)]
+pub enum ConflictKind {
+	ShiftReduce,
+	ReduceReduce
+}
+
+/*
+	Two or more actions for the same symbol in the same set. The rules are the items in the set that compete,
+	shifting rules have the dot before the symbol and reducing rules have the dot at the end.
+*/
+#[derive(Debug)]
+pub struct Conflict {
+	pub kind: ConflictKind,
+	pub set_index: usize,
+	pub symbol: String,
+	pub rules: Vec<TableRule>
+}
+
+impl std::fmt::Display for Conflict {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let kind = match self.kind {
+			ConflictKind::ShiftReduce => "Shift/reduce",
+			ConflictKind::ReduceReduce => "Reduce/reduce"
+		};
+
+		write!(f, "  {} conflict in set {} on symbol '{}' between:", kind, self.set_index, self.symbol)?;
+		for rule in &self.rules {
+			write!(f, "\n    {}", rule)?;
+		}
+
+		Ok(())
+	}
 }
 
 pub enum Action<'a> {
 	Accept,
 	Shift(usize),
 	Reduce(&'a TableRule),
+	#[allow(dead_code)]
 	Error(ParseTableError)
 }
 
@@ -23,30 +79,10 @@ struct Set {
 
 impl Set {
 	pub fn is_same_kernel(&self, other: &Set) -> bool {
-		let longest_rules = if self.rules.len() < other.rules.len() { &other.rules } else { &self.rules };
-		let shortest_rules = if self.rules.len() < other.rules.len() { &self.rules } else { &other.rules };
-
-		for rule1 in longest_rules {
-			if !rule1.is_kernel_rule {
-				continue;
-			}
-
-			let mut found_matching_rule = false;
-			for rule2 in shortest_rules {
-				if rule2.is_kernel_rule
-					&& rule1.left == rule2.left
-					&& rule1.next_symbol_index == rule2.next_symbol_index
-					&& rule1.right == rule2.right {
-					found_matching_rule = true;
-				}
-			}
-
-			if !found_matching_rule {
-				return false;
-			}
-		}
+		let kernel: Vec<&TableRule> = self.rules.iter().filter(|r| r.is_kernel_rule).collect();
+		let other_kernel: Vec<&TableRule> = other.rules.iter().filter(|r| r.is_kernel_rule).collect();
 
-		return true;
+		kernel.len() == other_kernel.len() && kernel.iter().all(|rule1| other_kernel.iter().any(|rule2| rule1.is_same_item(rule2)))
 	}
 }
 
@@ -62,8 +98,8 @@ pub struct TableRule {
 impl TableRule {
 	pub fn new(left: String, right: Vec<String>) -> Self {
 		TableRule {
-			left: left,
-			right: right,
+			left,
+			right,
 			next_symbol_index: 0,
 			next_set_index: None,
 			is_kernel_rule: true
@@ -75,17 +111,17 @@ impl TableRule {
 		new_rule.next_symbol_index = self.next_symbol_index + 1;
 		new_rule.next_set_index = None;
 		new_rule.is_kernel_rule = true;
-		return new_rule
+		new_rule
 	}
 
 	pub fn clone_as_expanded_rule(&self) -> TableRule {
 		let mut new_rule = self.clone();
 		new_rule.is_kernel_rule = false;
-		return new_rule
+		new_rule
 	}
 
 	pub fn can_advance(&self) -> bool {
-		self.next_symbol_index < self.right.len() && self.right.get(self.next_symbol_index).unwrap() != "$"
+		self.next_symbol_index < self.right.len() && self.right[self.next_symbol_index] != "$"
 	}
 
 	pub fn can_expand(&self) -> bool {
@@ -96,6 +132,14 @@ impl TableRule {
 		self.next_symbol_index == self.right.len()
 	}
 
+	pub fn left(&self) -> &str {
+		&self.left
+	}
+
+	pub fn right(&self) -> &[String] {
+		&self.right
+	}
+
 	pub fn matches_lookahead(&self, lookahead: &str) -> bool {
 		if let Some(symbol) = self.right.get(self.next_symbol_index) {
 			return symbol == lookahead;
@@ -103,27 +147,59 @@ impl TableRule {
 
 		false
 	}
+
+	// Same position in the same rule, regardless of which set it leads to
+	fn is_same_item(&self, other: &TableRule) -> bool {
+		self.left == other.left && self.right == other.right && self.next_symbol_index == other.next_symbol_index
+	}
+
+	fn next_symbol(&self) -> Option<&String> {
+		self.right.get(self.next_symbol_index)
+	}
+}
+
+impl std::fmt::Display for TableRule {
+	// The dot is written as "*", "A -> B * C" has B consumed and expects C next
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} ->", self.left)?;
+		for (idx, right) in self.right.iter().enumerate() {
+			if self.next_symbol_index == idx {
+				write!(f, " *")?;
+			}
+			write!(f, " {}", right)?;
+		}
+		if self.is_reducable() {
+			write!(f, " *")?;
+		}
+
+		Ok(())
+	}
 }
 
+/*
+	SLR(1) parse table. The sets are the LR(0) item sets, and a set reduces a rule only when the lookahead
+	is in the FOLLOW set of the rule's left side. Rules sharing a prefix can therefore be told apart by the symbol after it.
+*/
 pub struct ParseTable {
 	rules: Vec<TableRule>,
 	sets: Vec<Set>,
-	possible_reductions: HashMap<usize, TableRule>
+	// Set index -> lookahead symbol -> rule to reduce
+	possible_reductions: HashMap<usize, HashMap<String, TableRule>>
 }
 
 impl ParseTable {
-	pub fn new(rules: Vec<TableRule>) -> Self {
+	pub fn new(rules: Vec<TableRule>) -> Result<Self, ParseTableError> {
 		let mut parse_table = ParseTable {
-			rules: rules,
+			rules,
 			sets: Vec::new(),
 			possible_reductions: HashMap::new()
 		};
 
-		parse_table.construct().unwrap();
-		return parse_table;
+		parse_table.construct()?;
+		Ok(parse_table)
 	}
 
-	pub fn get_action(&self, set_index: usize, symbol: &str) -> Action {
+	pub fn get_action(&self, set_index: usize, symbol: &str) -> Action<'_> {
 		if let Some(set) = self.sets.get(set_index) {
 			for rule in &set.rules {
 				if rule.matches_lookahead(symbol) && symbol == "$" {
@@ -135,7 +211,7 @@ impl ParseTable {
 				}
 			}
 
-			if let Some(rule) = self.possible_reductions.get(&set_index) {
+			if let Some(rule) = self.possible_reductions.get(&set_index).and_then(|reductions| reductions.get(symbol)) {
 				return Action::Reduce(rule);
 			}
 
@@ -145,26 +221,16 @@ impl ParseTable {
 		Action::Error(ParseTableError::InvalidSetIndex)
 	}
 
+	#[allow(dead_code)]
 	pub fn print_table(&self) {
 		for set in &self.sets {
 			println!("\nSet {}:", set.index);
 			for rule in &set.rules {
-				print!("  {} -> ", rule.left);
-				let mut placed_dot = false;
-				for (idx, right) in rule.right.iter().enumerate() {
-					if rule.next_symbol_index == idx {
-						print!("*");
-						placed_dot = true;
-					}
-					print!(" {}", right);
-				}
-				if !placed_dot {
-					print!("*");
-				}
+				print!("  {}", rule);
 				print!("  (S{})", rule.next_set_index.unwrap_or(99999));
 				print!("  (Kernel: {:?})", rule.is_kernel_rule);
 
-				println!("")
+				println!()
 			}
 		}
 	}
@@ -187,9 +253,7 @@ impl ParseTable {
 			set_index += 1;
 		}
 
-		self.populate_reductions();
-
-		Ok(())
+		self.populate_reductions()
 	}
 
 	fn add_start_rule(&mut self) -> Result<&TableRule, ParseTableError> {
@@ -230,52 +294,304 @@ impl ParseTable {
 	}
 
 	fn get_advanced_sets(&self, set: &mut Set, mut next_free_set_index: usize, set_queue: &VecDeque<Set>) -> Vec<Set> {
-		let mut advanced_sets: Vec<Set> = Vec::new();
-
-		for rule in &mut set.rules {
+		// Group the advanced rules by the symbol they advance over, one kernel per symbol
+		let mut kernels: Vec<(String, Vec<TableRule>)> = Vec::new();
+		for rule in &set.rules {
 			// Only check rules which has something to advance
 			if !rule.can_advance() {
 				continue;
 			}
 
-			// Check if a set with the "DOT Symbol" has already been added, add to that set if so
-			let mut added_to_set = false;
-			for advanced_set in &mut advanced_sets {
-				let advanced_rule = advanced_set.rules.first().unwrap();
-				if advanced_rule.right.get(advanced_rule.next_symbol_index - 1).unwrap() == rule.right.get(rule.next_symbol_index).unwrap() {
-					advanced_set.rules.push(rule.clone_and_advance());
-					rule.next_set_index = Some(advanced_set.index);
-					added_to_set = true;
-					break;
+			let symbol = rule.next_symbol().unwrap();
+			if let Some((_, kernel)) = kernels.iter_mut().find(|(s, _)| s == symbol) {
+				kernel.push(rule.clone_and_advance());
+			} else {
+				kernels.push((symbol.clone(), vec![rule.clone_and_advance()]));
+			}
+		}
+
+		// Reuse an already known set with the same kernel, otherwise a new set is created
+		let mut advanced_sets: Vec<Set> = Vec::new();
+		for (symbol, kernel) in kernels {
+			let candidate = Set{index: next_free_set_index, rules: kernel};
+			let existing_index = self.sets.iter()
+				.chain(set_queue.iter())
+				.chain(advanced_sets.iter())
+				.chain(std::iter::once(&*set))
+				.find(|s| s.is_same_kernel(&candidate))
+				.map(|s| s.index);
+
+			let target_index = match existing_index {
+				Some(index) => index,
+				None => {
+					advanced_sets.push(candidate);
+					next_free_set_index += 1;
+					next_free_set_index - 1
+				}
+			};
+
+			for rule in &mut set.rules {
+				if rule.can_advance() && *rule.next_symbol().unwrap() == symbol {
+					rule.next_set_index = Some(target_index);
 				}
 			}
+		}
 
-			if !added_to_set {
-				advanced_sets.push(Set{index: next_free_set_index, rules: vec![rule.clone_and_advance()]});
-				rule.next_set_index = Some(next_free_set_index);
-				next_free_set_index += 1;
+		advanced_sets
+	}
+
+	// Reductions are added for every symbol in FOLLOW, any symbol with more than one action is reported as a conflict
+	fn populate_reductions(&mut self) -> Result<(), ParseTableError> {
+		let follow_sets = self.get_follow_sets();
+		let mut conflicts = Vec::new();
+
+		for set in &self.sets {
+			let mut reductions: HashMap<String, Vec<&TableRule>> = HashMap::new();
+			for rule in set.rules.iter().filter(|rule| rule.is_reducable()) {
+				for symbol in follow_sets.get(&rule.left).into_iter().flatten() {
+					reductions.entry(symbol.clone()).or_default().push(rule);
+				}
+			}
+
+			for (symbol, reducing_rules) in &reductions {
+				let shifting_rules: Vec<&TableRule> = set.rules.iter().filter(|rule| rule.matches_lookahead(symbol)).collect();
+				if shifting_rules.is_empty() && reducing_rules.len() == 1 {
+					continue;
+				}
+
+				conflicts.push(Conflict{
+					kind: if shifting_rules.is_empty() { ConflictKind::ReduceReduce } else { ConflictKind::ShiftReduce },
+					set_index: set.index,
+					symbol: symbol.clone(),
+					rules: shifting_rules.into_iter().chain(reducing_rules.iter().copied()).cloned().collect()
+				});
+			}
+
+			if !reductions.is_empty() {
+				let reductions = reductions.into_iter().map(|(symbol, rules)| (symbol, rules[0].clone())).collect();
+				self.possible_reductions.insert(set.index, reductions);
 			}
 		}
 
-		let filtered_sets = advanced_sets
-			.iter()
-			.filter(|new_set| {
-				!self.sets.iter().any(|s| s.is_same_kernel(&new_set))
-				&& !set_queue.iter().any(|s| s.is_same_kernel(&new_set))
-				&& !set.is_same_kernel(&new_set)})
-			.cloned();
+		if !conflicts.is_empty() {
+			conflicts.sort_by(|a, b| a.set_index.cmp(&b.set_index).then(a.symbol.cmp(&b.symbol)));
+			return Err(ParseTableError::Conflicts(conflicts));
+		}
+
+		Ok(())
+	}
 
-		filtered_sets.collect()
+	fn is_non_terminal(&self, symbol: &str) -> bool {
+		self.rules.iter().any(|rule| rule.left == symbol)
 	}
 
-	fn populate_reductions(&mut self) {
-		for set in &self.sets {
-			for rule in &set.rules {
-				if rule.is_reducable() {
-					self.possible_reductions.insert(set.index, rule.clone());
-					break;
+	// Non terminals that can be reduced without consuming any tokens
+	fn get_nullable_symbols(&self) -> HashSet<String> {
+		let mut nullable = HashSet::new();
+
+		let mut changed = true;
+		while changed {
+			changed = false;
+			for rule in &self.rules {
+				if !nullable.contains(&rule.left) && rule.right.iter().all(|symbol| nullable.contains(symbol)) {
+					nullable.insert(rule.left.clone());
+					changed = true;
+				}
+			}
+		}
+
+		nullable
+	}
+
+	// FIRST of every non terminal, the terminals that a rule with the non terminal as left side can start with
+	fn get_first_sets(&self, nullable: &HashSet<String>) -> HashMap<String, HashSet<String>> {
+		let mut first_sets: HashMap<String, HashSet<String>> = HashMap::new();
+
+		let mut changed = true;
+		while changed {
+			changed = false;
+			for rule in &self.rules {
+				let first = self.get_first_of_sequence(&rule.right, &first_sets, nullable);
+				let entry = first_sets.entry(rule.left.clone()).or_default();
+				let length = entry.len();
+				entry.extend(first);
+				changed |= entry.len() != length;
+			}
+		}
+
+		first_sets
+	}
+
+	fn get_first_of_sequence(&self, symbols: &[String], first_sets: &HashMap<String, HashSet<String>>, nullable: &HashSet<String>) -> HashSet<String> {
+		let mut first = HashSet::new();
+		for symbol in symbols {
+			if self.is_non_terminal(symbol) {
+				first.extend(first_sets.get(symbol).into_iter().flatten().cloned());
+			} else {
+				first.insert(symbol.clone());
+			}
+
+			if !nullable.contains(symbol) {
+				break;
+			}
+		}
+
+		first
+	}
+
+	// FOLLOW of every non terminal, the terminals that can come directly after it. The start rule adds "$"
+	fn get_follow_sets(&self) -> HashMap<String, HashSet<String>> {
+		let nullable = self.get_nullable_symbols();
+		let first_sets = self.get_first_sets(&nullable);
+		let mut follow_sets: HashMap<String, HashSet<String>> = HashMap::new();
+
+		let mut changed = true;
+		while changed {
+			changed = false;
+			for rule in &self.rules {
+				for (idx, symbol) in rule.right.iter().enumerate() {
+					if !self.is_non_terminal(symbol) {
+						continue;
+					}
+
+					let rest = &rule.right[idx + 1..];
+					let mut follow = self.get_first_of_sequence(rest, &first_sets, &nullable);
+					if rest.iter().all(|s| nullable.contains(s)) {
+						follow.extend(follow_sets.get(&rule.left).into_iter().flatten().cloned());
+					}
+
+					let entry = follow_sets.entry(symbol.clone()).or_default();
+					let length = entry.len();
+					entry.extend(follow);
+					changed |= entry.len() != length;
 				}
 			}
 		}
+
+		follow_sets
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// "S -> A B" is written as ("S", "A B"), an empty right side is an empty rule
+	fn rules(rules: &[(&str, &str)]) -> Vec<TableRule> {
+		rules.iter()
+			.map(|(left, right)| TableRule::new(left.to_string(), right.split_whitespace().map(|symbol| symbol.to_string()).collect()))
+			.collect()
+	}
+
+	fn set(symbols: &[&str]) -> HashSet<String> {
+		symbols.iter().map(|symbol| symbol.to_string()).collect()
+	}
+
+	#[test]
+	fn follow_sets_skip_nullable_symbols() {
+		let table = ParseTable::new(rules(&[
+			("S", "A B C"),
+			("A", "X"),
+			("B", "Y"),
+			("B", ""),
+			("C", "Z")
+		])).unwrap();
+
+		let follow_sets = table.get_follow_sets();
+		assert_eq!(follow_sets["A"], set(&["Y", "Z"]));
+		assert_eq!(follow_sets["B"], set(&["Z"]));
+		assert_eq!(follow_sets["C"], set(&["$"]));
+		assert_eq!(follow_sets["S"], set(&["$"]));
 	}
-}
\ No newline at end of file
+
+	// Both rules reduce after X, which one is told by the symbol after it. LR(0) can't tell them apart
+	#[test]
+	fn reductions_are_chosen_by_lookahead() {
+		let table = ParseTable::new(rules(&[
+			("S", "A Y"),
+			("S", "B Z"),
+			("A", "X"),
+			("B", "X")
+		])).unwrap();
+
+		let after_x = match table.get_action(0, "X") {
+			Action::Shift(set_index) => set_index,
+			_ => panic!("X is not shifted from the start")
+		};
+
+		assert!(matches!(table.get_action(after_x, "Y"), Action::Reduce(rule) if rule.left() == "A"));
+		assert!(matches!(table.get_action(after_x, "Z"), Action::Reduce(rule) if rule.left() == "B"));
+		assert!(matches!(table.get_action(after_x, "$"), Action::Error(_)));
+	}
+
+	fn conflicts(rules: Vec<TableRule>) -> Vec<Conflict> {
+		match ParseTable::new(rules) {
+			Err(ParseTableError::Conflicts(conflicts)) => conflicts,
+			Err(err) => panic!("Expected conflicts, got: {}", err),
+			Ok(_) => panic!("Expected conflicts, the table was built")
+		}
+	}
+
+	#[test]
+	fn grammar_without_conflicts_builds_a_table() {
+		let table = ParseTable::new(rules(&[
+			("SENTENCE", "ADD TEXT"),
+			("SENTENCE", "ADD TO TODO TEXT"),
+			("TEXT", "TEXT WORD"),
+			("TEXT", "WORD")
+		]));
+
+		assert!(table.is_ok());
+	}
+
+	// "1 + 2 + 3" can be read as "(1 + 2) + 3" and as "1 + (2 + 3)"
+	#[test]
+	fn shift_reduce_conflict_names_the_set_rules_and_symbol() {
+		let conflicts = conflicts(rules(&[
+			("E", "E PLUS E"),
+			("E", "NUM")
+		]));
+
+		assert_eq!(conflicts.len(), 1);
+		let conflict = &conflicts[0];
+		assert_eq!(conflict.kind, ConflictKind::ShiftReduce);
+		assert_eq!(conflict.symbol, "PLUS");
+
+		let rules: Vec<String> = conflict.rules.iter().map(|rule| rule.to_string()).collect();
+		assert_eq!(rules, vec!["E -> E * PLUS E", "E -> E PLUS E *"]);
+
+		let report = conflict.to_string();
+		assert!(report.contains(&format!("set {}", conflict.set_index)), "{}", report);
+		assert!(report.contains("'PLUS'"), "{}", report);
+		assert!(report.contains("E -> E PLUS E *"), "{}", report);
+	}
+
+	#[test]
+	fn reduce_reduce_conflict_names_both_rules() {
+		let conflicts = conflicts(rules(&[
+			("S", "A"),
+			("S", "B"),
+			("A", "X"),
+			("B", "X")
+		]));
+
+		assert_eq!(conflicts.len(), 1);
+		assert_eq!(conflicts[0].kind, ConflictKind::ReduceReduce);
+		assert_eq!(conflicts[0].symbol, "$");
+
+		let rules: Vec<String> = conflicts[0].rules.iter().map(|rule| rule.to_string()).collect();
+		assert!(rules.contains(&"A -> X *".to_string()) && rules.contains(&"B -> X *".to_string()), "{:?}", rules);
+	}
+
+	#[test]
+	fn all_conflicts_are_reported() {
+		let err = ParseTable::new(rules(&[
+			("E", "E PLUS E"),
+			("E", "E TIMES E"),
+			("E", "NUM")
+		])).err().unwrap();
+
+		assert!(matches!(&err, ParseTableError::Conflicts(conflicts) if conflicts.len() > 1));
+		assert!(err.to_string().starts_with("The grammar is ambiguous"));
+	}
+}
@@ -1,4 +1,4 @@
-use crate::{parse_table::{Action, ParseTable, TableRule}, tokenizer::Token};
+use crate::{parse_table::{Action, ParseTable, ParseTableError, TableRule}, tokenizer::Token};
 
 pub struct Rule {
 	name: String,
@@ -8,20 +8,64 @@ pub struct Rule {
 impl Rule {
 	pub fn new(name: String, rule: String) -> Self {
 		Rule {
-			name: name,
-			rule: rule
+			name,
+			rule
 		}
 	}
 }
 
 pub struct Node {
-	name: String,
-	children: Vec<Symbol>
+	pub name: String,
+	pub children: Vec<Symbol>
+}
+
+impl Node {
+	// All token values below the node, in the order they were written
+	pub fn values(&self) -> Vec<&str> {
+		let mut values = Vec::new();
+		for child in &self.children {
+			match child {
+				Symbol::NonTerminal(node) => values.extend(node.values()),
+				Symbol::Terminal(token) => values.push(token.value.as_str())
+			}
+		}
+
+		values
+	}
+
+	// All tokens below the node, in the order they were written
+	pub fn tokens(&self) -> Vec<&Token> {
+		let mut tokens = Vec::new();
+		for child in &self.children {
+			match child {
+				Symbol::NonTerminal(node) => tokens.extend(node.tokens()),
+				Symbol::Terminal(token) => tokens.push(token)
+			}
+		}
+
+		tokens
+	}
+
+	// The first child token with the given name, does not search deeper than the direct children
+	pub fn token(&self, name: &str) -> Option<&Token> {
+		self.children.iter().find_map(|child| match child {
+			Symbol::Terminal(token) if token.name == name => Some(token),
+			_ => None
+		})
+	}
+
+	// The first child node with the given name, does not search deeper than the direct children
+	pub fn child(&self, name: &str) -> Option<&Node> {
+		self.children.iter().find_map(|child| match child {
+			Symbol::NonTerminal(node) if node.name == name => Some(node),
+			_ => None
+		})
+	}
 }
 
 pub enum Symbol {
 	NonTerminal(Node),
-	Terminal(String)
+	Terminal(Token)
 }
 
 pub struct Parser {
@@ -29,44 +73,252 @@ pub struct Parser {
 }
 
 impl Parser {
-	pub fn new(rules: Vec<Rule>) -> Self {
-		Parser {
-			parse_table: ParseTable::new(Self::transform_rules(rules))
-		}
+	pub fn new(rules: Vec<Rule>) -> Result<Self, ParseTableError> {
+		Ok(Parser {
+			parse_table: ParseTable::new(Self::transform_rules(rules)?)?
+		})
 	}
 
+	/*
+		Runs the tokens through the parse table as a shift-reduce parser.
+		The state stack holds the set index for every symbol on the symbol stack, with the start set at the bottom.
+		Returns the tree of the start rule, or None if the tokens are not part of the grammar.
+	*/
 	pub fn parse(&self, input: Vec<Token>) -> Option<Node> {
-		let mut current_input_idx = 0;
+		let mut input = input.into_iter().peekable();
+		let mut states: Vec<usize> = vec![0];
 		let mut stack: Vec<Symbol> = Vec::new();
 
-		// while self.parse_table.get_action(0, input.get(current_input_idx)?.) != Action::Accept {
+		loop {
+			let lookahead = input.peek().map_or("$", |token| token.name.as_str());
+
+			match self.parse_table.get_action(*states.last()?, lookahead) {
+				Action::Accept => {
+					return match stack.pop()? {
+						Symbol::NonTerminal(node) => Some(node),
+						Symbol::Terminal(_) => None
+					};
+				},
+				Action::Shift(next_set_index) => {
+					stack.push(Symbol::Terminal(input.next()?));
+					states.push(next_set_index);
+				},
+				Action::Reduce(rule) => {
+					let length = rule.right().len();
+					let children = Self::flatten_auxiliary(stack.split_off(stack.len().checked_sub(length)?));
+					states.truncate(states.len() - length);
 
-		// }
+					// The reduced rule is "shifted" as a non terminal from the uncovered set
+					match self.parse_table.get_action(*states.last()?, rule.left()) {
+						Action::Shift(next_set_index) => states.push(next_set_index),
+						_ => return None
+					}
 
-		None
+					stack.push(Symbol::NonTerminal(Node{
+						name: rule.left().to_string(),
+						children
+					}));
+				},
+				Action::Error(_) => return None
+			}
+		}
 	}
 
+	// Auxiliary nodes from EBNF expansion are replaced by their children, so the tree matches the rule as it was written
+	fn flatten_auxiliary(children: Vec<Symbol>) -> Vec<Symbol> {
+		let mut flattened = Vec::new();
+		for child in children {
+			match child {
+				Symbol::NonTerminal(node) if node.name.contains('#') => flattened.extend(node.children),
+				child => flattened.push(child)
+			}
+		}
 
+		flattened
+	}
 
-	fn transform_rules(rules: Vec<Rule>) -> Vec<TableRule> {
+	fn transform_rules(rules: Vec<Rule>) -> Result<Vec<TableRule>, ParseTableError> {
 		let mut table_rules = Vec::new();
+		let mut auxiliary_count = 0;
 		for rule in rules {
-			table_rules.append(&mut Self::adapt_rule(rule));
+			table_rules.append(&mut Self::adapt_rule(rule, &mut auxiliary_count)?);
+		}
+
+		Ok(table_rules)
+	}
+
+	/*
+		Expands the EBNF operators of a rule into plain rules, the first rule returned is the adapted rule itself.
+		"( )" groups symbols, "?" makes the preceding symbol or group optional, "*" repeats it zero or more times and "+" one or more times.
+		The auxiliary rules are named "NAME#N" and are flattened into their parent when parsing.
+	*/
+	fn adapt_rule(rule: Rule, auxiliary_count: &mut usize) -> Result<Vec<TableRule>, ParseTableError> {
+		let tokens = Self::split_rule(&rule.rule);
+		let mut auxiliary_rules = Vec::new();
+		let mut position = 0;
+
+		let right = Self::expand_sequence(&rule.name, &tokens, &mut position, &mut auxiliary_rules, auxiliary_count)?;
+		if position != tokens.len() {
+			// Only a ")" without a matching "(" stops the sequence early
+			return Err(ParseTableError::InvalidTableRule);
+		}
+
+		let mut table_rules = vec![TableRule::new(rule.name, right)];
+		table_rules.append(&mut auxiliary_rules);
+		Ok(table_rules)
+	}
+
+	// "(AT TIME)? WORD+" -> ["(", "AT", "TIME", ")", "?", "WORD", "+"]
+	fn split_rule(rule: &str) -> Vec<String> {
+		let mut tokens = Vec::new();
+		let mut symbol = String::new();
+		for c in rule.chars() {
+			if c.is_whitespace() || matches!(c, '(' | ')' | '?' | '*' | '+') {
+				if !symbol.is_empty() {
+					tokens.push(std::mem::take(&mut symbol));
+				}
+				if !c.is_whitespace() {
+					tokens.push(c.to_string());
+				}
+			} else {
+				symbol.push(c);
+			}
+		}
+
+		if !symbol.is_empty() {
+			tokens.push(symbol);
+		}
+
+		tokens
+	}
+
+	fn expand_sequence(name: &str, tokens: &[String], position: &mut usize, auxiliary_rules: &mut Vec<TableRule>, auxiliary_count: &mut usize) -> Result<Vec<String>, ParseTableError> {
+		let mut symbols = Vec::new();
+
+		while let Some(token) = tokens.get(*position) {
+			let mut item = match token.as_str() {
+				")" => break,
+				"?" | "*" | "+" => return Err(ParseTableError::InvalidTableRule),
+				"(" => {
+					*position += 1;
+					let group = Self::expand_sequence(name, tokens, position, auxiliary_rules, auxiliary_count)?;
+					if tokens.get(*position).map(|t| t.as_str()) != Some(")") || group.is_empty() {
+						return Err(ParseTableError::InvalidTableRule);
+					}
+					group
+				},
+				symbol => vec![symbol.to_string()]
+			};
+			*position += 1;
+
+			if let Some(operator @ ("?" | "*" | "+")) = tokens.get(*position).map(|t| t.as_str()) {
+				*auxiliary_count += 1;
+				let auxiliary = format!("{}#{}", name, auxiliary_count);
+
+				// Repetitions are left recursive, which keeps the parse stack small
+				let mut repeated = vec![auxiliary.clone()];
+				repeated.extend(item.iter().cloned());
+				match operator {
+					"?" => {
+						auxiliary_rules.push(TableRule::new(auxiliary.clone(), item));
+						auxiliary_rules.push(TableRule::new(auxiliary.clone(), Vec::new()));
+					},
+					"*" => {
+						auxiliary_rules.push(TableRule::new(auxiliary.clone(), repeated));
+						auxiliary_rules.push(TableRule::new(auxiliary.clone(), Vec::new()));
+					},
+					_ => {
+						auxiliary_rules.push(TableRule::new(auxiliary.clone(), repeated));
+						auxiliary_rules.push(TableRule::new(auxiliary.clone(), item));
+					}
+				}
+
+				item = vec![auxiliary];
+				*position += 1;
+			}
+
+			symbols.append(&mut item);
 		}
 
-		return table_rules
+		Ok(symbols)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parser(rules: &[(&str, &str)]) -> Parser {
+		let rules = rules.iter().map(|(name, rule)| Rule::new(name.to_string(), rule.to_string())).collect();
+		Parser::new(rules).unwrap()
+	}
+
+	// "REMIND:remind" is a token named REMIND with the value "remind"
+	fn tokens(input: &str) -> Vec<Token> {
+		input.split_whitespace()
+			.map(|token| {
+				let (name, value) = token.split_once(':').unwrap();
+				Token{name: name.to_string(), value: value.to_string()}
+			})
+			.collect()
+	}
+
+	fn names(node: &Node) -> Vec<&str> {
+		node.children.iter().map(|child| match child {
+			Symbol::NonTerminal(node) => node.name.as_str(),
+			Symbol::Terminal(token) => token.name.as_str()
+		}).collect()
 	}
 
-	fn adapt_rule(rule: Rule) -> Vec<TableRule> {
-		// TODO: Add expansion of "( )", "+", and "*" as MVP
-		return vec![
-			TableRule::new(
-				rule.name,
-				rule.rule
-					.split(' ')
-					.map(|x| x.to_string())
-					.collect()
-				)
-			]
+	fn expanded(rule: &str) -> Vec<String> {
+		Parser::adapt_rule(Rule::new("A".to_string(), rule.to_string()), &mut 0)
+			.unwrap()
+			.iter()
+			.map(|rule| rule.to_string())
+			.collect()
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn operators_expand_into_helper_rules() {
+		assert_eq!(expanded("B?"), vec!["A -> * A#1", "A#1 -> * B", "A#1 -> *"]);
+		assert_eq!(expanded("B*"), vec!["A -> * A#1", "A#1 -> * A#1 B", "A#1 -> *"]);
+		assert_eq!(expanded("B+"), vec!["A -> * A#1", "A#1 -> * A#1 B", "A#1 -> * B"]);
+		assert_eq!(expanded("B (C D)? E"), vec!["A -> * B A#1 E", "A#1 -> * C D", "A#1 -> *"]);
+	}
+
+	#[test]
+	fn invalid_operators_are_an_error() {
+		for rule in ["B)", "(B", "?", "B ()?", "B + +"] {
+			assert!(Parser::adapt_rule(Rule::new("A".to_string(), rule.to_string()), &mut 0).is_err(), "{}", rule);
+		}
+	}
+
+	#[test]
+	fn repetition_is_flattened_into_the_rule() {
+		let parser = parser(&[("CONTENT", "WORD+")]);
+
+		let tree = parser.parse(tokens("WORD:buy WORD:some WORD:milk")).unwrap();
+		assert_eq!(tree.name, "CONTENT");
+		assert_eq!(names(&tree), vec!["WORD", "WORD", "WORD"]);
+		assert_eq!(tree.values(), vec!["buy", "some", "milk"]);
+
+		assert!(parser.parse(Vec::new()).is_none());
+	}
+
+	#[test]
+	fn optional_group_is_flattened_into_the_rule() {
+		let parser = parser(&[
+			("REMIND", "COMMAND SUBJECT (AT TIME)? TO CONTENT"),
+			("CONTENT", "WORD+")
+		]);
+
+		let tree = parser.parse(tokens("COMMAND:remind SUBJECT:me AT:at TIME:5pm TO:to WORD:water WORD:plants")).unwrap();
+		assert_eq!(names(&tree), vec!["COMMAND", "SUBJECT", "AT", "TIME", "TO", "CONTENT"]);
+		assert_eq!(tree.child("CONTENT").unwrap().values(), vec!["water", "plants"]);
+
+		let tree = parser.parse(tokens("COMMAND:remind SUBJECT:me TO:to WORD:water")).unwrap();
+		assert_eq!(names(&tree), vec!["COMMAND", "SUBJECT", "TO", "CONTENT"]);
+
+		assert!(parser.parse(tokens("COMMAND:remind SUBJECT:me AT:at TO:to WORD:water")).is_none());
+	}
+}
@@ -1,11 +1,102 @@
-pub fn get_config_path() -> std::path::PathBuf {
-	get_home_dir().join(".hey/config.yaml")
+/*
+	Where hey keeps its files.
+
+	The config and grammar are placed in the config directory:
+		1. $HEY_HOME, a relative path is read from the current directory
+		2. ~/.hey, if it already exists from an earlier version
+		3. $XDG_CONFIG_HOME/hey, or ~/.config/hey
+	The todos, notes and reminders are placed in the data directory:
+		1. The --data-dir flag
+		2. $HEY_HOME
+		3. "storage" in the config
+		4. ~/.hey, if it already exists from an earlier version
+		5. $XDG_DATA_HOME/hey, or ~/.local/share/hey
+*/
+
+use std::{path::PathBuf, sync::OnceLock};
+
+use crate::config;
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+// Set from the --data-dir flag, before any file is read
+pub fn set_data_dir(path: PathBuf) {
+	let _ = DATA_DIR_OVERRIDE.set(path);
+}
+
+pub fn get_config_path() -> anyhow::Result<PathBuf> {
+	Ok(get_config_dir()?.join("config.yaml"))
+}
+
+// A file in the data directory, like "todo.yaml"
+pub fn get_data_path(file_name: &str) -> anyhow::Result<PathBuf> {
+	Ok(get_data_dir()?.join(file_name))
+}
+
+pub fn get_grammar_path() -> anyhow::Result<PathBuf> {
+	Ok(get_config_dir()?.join("grammar"))
 }
 
-pub fn get_todo_path() -> std::path::PathBuf {
-	get_home_dir().join(".hey/todo.yaml")
+fn get_config_dir() -> anyhow::Result<PathBuf> {
+	if let Some(hey_home) = get_hey_home()? {
+		return Ok(hey_home);
+	}
+	if let Some(legacy) = get_legacy_dir() {
+		return Ok(legacy);
+	}
+
+	match get_env_dir("XDG_CONFIG_HOME") {
+		Some(config_home) => Ok(config_home.join("hey")),
+		None => Ok(get_home_dir()?.join(".config/hey"))
+	}
+}
+
+fn get_data_dir() -> anyhow::Result<PathBuf> {
+	if let Some(data_dir) = DATA_DIR_OVERRIDE.get() {
+		return Ok(data_dir.clone());
+	}
+	if let Some(hey_home) = get_hey_home()? {
+		return Ok(hey_home);
+	}
+	if let Some(storage) = &config::get().storage {
+		return match storage.strip_prefix("~/") {
+			Some(relative) => Ok(get_home_dir()?.join(relative)),
+			None => Ok(PathBuf::from(storage))
+		};
+	}
+	if let Some(legacy) = get_legacy_dir() {
+		return Ok(legacy);
+	}
+
+	match get_env_dir("XDG_DATA_HOME") {
+		Some(data_home) => Ok(data_home.join("hey")),
+		None => Ok(get_home_dir()?.join(".local/share/hey"))
+	}
+}
+
+// Everything was kept in ~/.hey before the XDG directories were used, it is still used when it exists
+fn get_legacy_dir() -> Option<PathBuf> {
+	let legacy = dirs::home_dir()?.join(".hey");
+	legacy.is_dir().then_some(legacy)
 }
 
-fn get_home_dir() -> std::path::PathBuf {
-	dirs::home_dir().expect("A home directory could not be found to place the config. Cannot recover, please report this error if happens.")
-}
\ No newline at end of file
+// Unlike the XDG variables a relative HEY_HOME is used, from the current directory
+fn get_hey_home() -> anyhow::Result<Option<PathBuf>> {
+	match std::env::var_os("HEY_HOME").filter(|path| !path.is_empty()) {
+		Some(path) => std::path::absolute(&path)
+			.map(Some)
+			.map_err(|err| anyhow::format_err!("HEY_HOME {} could not be resolved: {}", PathBuf::from(path).display(), err)),
+		None => Ok(None)
+	}
+}
+
+// Empty and relative paths are ignored, as the XDG specification asks
+fn get_env_dir(name: &str) -> Option<PathBuf> {
+	std::env::var_os(name)
+		.map(PathBuf::from)
+		.filter(|path| path.is_absolute())
+}
+
+fn get_home_dir() -> anyhow::Result<PathBuf> {
+	dirs::home_dir().ok_or(anyhow::format_err!("No home directory was found to keep the files of hey in, set HEY_HOME to the directory to use instead"))
+}
@@ -0,0 +1,72 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+use colored::Colorize;
+
+use crate::config;
+
+/*
+	Lets the user pick one of the items, returns the index of the picked item or None if nothing was picked.
+	The picker program from the config is used if it is installed, e.g. fzf, otherwise a numbered list is printed and the number is read from stdin.
+	The program gets the items as lines on stdin and writes the picked line to stdout.
+*/
+pub fn pick(items: &[String]) -> anyhow::Result<Option<usize>> {
+	if !std::io::stdin().is_terminal() {
+		return Err(anyhow::format_err!("Nothing to pick from without a terminal, give the item as an argument instead"));
+	}
+
+	let program = &config::get().picker;
+	if program == "builtin" {
+		return pick_with_prompt(items);
+	}
+
+	match pick_with_program(program, items) {
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => pick_with_prompt(items),
+		result => Ok(result?)
+	}
+}
+
+fn pick_with_program(program: &str, items: &[String]) -> std::io::Result<Option<usize>> {
+	let mut words = program.split_whitespace();
+	let mut picker = std::process::Command::new(words.next().unwrap_or("fzf"))
+		.args(words)
+		.stdin(std::process::Stdio::piped())
+		.stdout(std::process::Stdio::piped())
+		.spawn()?;
+
+	{
+		let stdin = picker.stdin.as_mut().expect("Failed to open stdin");
+		for item in items {
+			writeln!(stdin, "{}", item)?;
+		}
+	}
+
+	let output = picker.wait_with_output()?;
+	if !output.status.success() {
+		return Ok(None);
+	}
+
+	let selection = String::from_utf8_lossy(&output.stdout);
+	let selection = selection.trim_end_matches(['\n', '\r']);
+	Ok(items.iter().position(|item| item == selection))
+}
+
+fn pick_with_prompt(items: &[String]) -> anyhow::Result<Option<usize>> {
+	for (idx, item) in items.iter().enumerate() {
+		println!("{} {}", format!("{:>3})", idx + 1).bold(), item);
+	}
+
+	loop {
+		print!("Pick a number, or press enter to cancel: ");
+		std::io::stdout().flush()?;
+
+		let mut line = String::new();
+		if std::io::stdin().lock().read_line(&mut line)? == 0 || line.trim().is_empty() {
+			return Ok(None);
+		}
+
+		match line.trim().parse::<usize>() {
+			Ok(number) if (1..=items.len()).contains(&number) => return Ok(Some(number - 1)),
+			_ => println!("{}", format!("Pick a number between 1 and {}", items.len()).red())
+		}
+	}
+}
@@ -0,0 +1,377 @@
+/*
+	A rule for something that comes back, like "every monday", "every 2 weeks" or "every month until 2024-12-31".
+	Reminders and todos with a recurrence get their next occurrence when they are delivered or checked.
+*/
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::date;
+
+// "every weekday"
+pub const WEEKDAYS: [Weekday; 5] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct Recurrence {
+	#[serde(default = "one", deserialize_with = "read_every")]
+	pub every: u32,
+	pub unit: Unit,
+	// Only used by weekly recurrences, e.g. "every monday and friday"
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub weekdays: Vec<Weekday>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub until: Option<NaiveDate>,
+	/*
+		The day of the month a monthly recurrence falls on, a shorter month has it on its last day. It is set from the first
+		occurrence, so "every month" from January 31st stays on the 31st after it was on February 28th.
+	*/
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub day_of_month: Option<u32>
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+	Day,
+	Week,
+	Month
+}
+
+fn one() -> u32 {
+	1
+}
+
+// A rule read from a file that repeats every 0 days would never get to a next day
+fn read_every<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+	match <u32 as serde::Deserialize>::deserialize(deserializer)? {
+		0 => Err(serde::de::Error::custom("a recurrence has to repeat every 1 or more days, weeks or months")),
+		every => Ok(every)
+	}
+}
+
+impl Recurrence {
+	// The first day of the recurrence on or after the day, the day itself unless it's not one of the weekdays
+	pub fn first_on_or_after(&self, day: NaiveDate) -> Option<NaiveDate> {
+		let first = match self.weekdays.is_empty() {
+			true => day,
+			false => day.iter_days().take(7).find(|day| self.weekdays.contains(&day.weekday()))?
+		};
+
+		self.within_end(first)
+	}
+
+	// The day of the occurrence after the one on the day, None if the recurrence has ended
+	pub fn next_after(&self, day: NaiveDate) -> Option<NaiveDate> {
+		if self.every == 0 {
+			return None;
+		}
+
+		let next = match self.unit {
+			Unit::Day => day.checked_add_days(chrono::Days::new(self.every as u64))?,
+			Unit::Week if self.weekdays.is_empty() => day.checked_add_days(chrono::Days::new(self.every as u64 * 7))?,
+			// The next of the weekdays, in the same week or in the week "every" weeks later
+			Unit::Week => {
+				let week_start = |day: NaiveDate| day - chrono::Days::new(day.weekday().num_days_from_monday() as u64);
+				day.iter_days()
+					.skip(1)
+					.take(7 * self.every as usize)
+					.find(|next| self.weekdays.contains(&next.weekday()) && ((week_start(*next) - week_start(day)).num_days() / 7) % self.every as i64 == 0)?
+			},
+			Unit::Month => {
+				let next = day.checked_add_months(chrono::Months::new(self.every))?;
+				next.with_day(self.day_of_month.unwrap_or(day.day()).min(days_in_month(next)))?
+			}
+		};
+
+		self.within_end(next)
+	}
+
+	// The recurrence with its day of the month taken from the occurrence on the day, if it doesn't have one yet
+	pub fn anchored_on(&self, day: NaiveDate) -> Recurrence {
+		let mut recurrence = self.clone();
+		if recurrence.unit == Unit::Month {
+			recurrence.day_of_month.get_or_insert(day.day());
+		}
+		recurrence
+	}
+
+	// The first occurrence after the day, skipping the occurrences that were missed
+	pub fn next_after_skipping(&self, mut day: NaiveDate, today: NaiveDate) -> Option<NaiveDate> {
+		day = self.next_after(day)?;
+		while day <= today {
+			day = self.next_after(day)?;
+		}
+
+		Some(day)
+	}
+
+	fn within_end(&self, day: NaiveDate) -> Option<NaiveDate> {
+		match self.until {
+			Some(until) if day > until => None,
+			_ => Some(day)
+		}
+	}
+}
+
+/*
+	Reads a recurrence from the start of the words, returns it with the amount of words used.
+	Accepts "every day", "every week", "every month", "every 3 days", "every other week", "every weekday",
+	"every monday", "every mon, wed and fri" and "every 2 weeks on monday", optionally followed by "until DATE".
+	A relative end like "until friday" is read from today.
+*/
+pub fn parse<S: AsRef<str>>(words: &[S], today: NaiveDate) -> Option<(Recurrence, usize)> {
+	let words: Vec<String> = words.iter().map(|word| word.as_ref().to_lowercase().trim_end_matches(',').to_string()).collect();
+	if words.first()? != "every" {
+		return None;
+	}
+
+	let mut idx = 1;
+	let every = match words.get(idx)?.as_str() {
+		"other" => Some(2),
+		word => parse_count(word)
+	};
+	if every.is_some() {
+		idx += 1;
+	}
+
+	let mut recurrence = match words.get(idx)?.trim_end_matches('s') {
+		"day" => Recurrence{every: 1, unit: Unit::Day, weekdays: Vec::new(), until: None, day_of_month: None},
+		"week" => Recurrence{every: 1, unit: Unit::Week, weekdays: Vec::new(), until: None, day_of_month: None},
+		"month" => Recurrence{every: 1, unit: Unit::Month, weekdays: Vec::new(), until: None, day_of_month: None},
+		"weekday" if every.is_none() => Recurrence{
+			every: 1,
+			unit: Unit::Week,
+			weekdays: WEEKDAYS.to_vec(),
+			until: None,
+			day_of_month: None
+		},
+		_ if every.is_none() => {
+			let (weekdays, consumed) = parse_weekdays(&words[idx..])?;
+			idx += consumed - 1;
+			Recurrence{every: 1, unit: Unit::Week, weekdays, until: None, day_of_month: None}
+		},
+		_ => return None
+	};
+	idx += 1;
+	recurrence.every = every.unwrap_or(recurrence.every);
+
+	// "every 2 weeks on monday"
+	if recurrence.unit == Unit::Week && recurrence.weekdays.is_empty() && words.get(idx).is_some_and(|word| word == "on") {
+		if let Some((weekdays, consumed)) = parse_weekdays(&words[idx + 1..]) {
+			recurrence.weekdays = weekdays;
+			idx += consumed + 1;
+		}
+	}
+
+	if words.get(idx).is_some_and(|word| word == "until") {
+		if let Some(until) = words.get(idx + 1).and_then(|word| date::parse_day_on(word, today).ok()) {
+			recurrence.until = Some(until);
+			idx += 2;
+		}
+	}
+
+	Some((recurrence, idx))
+}
+
+// A whole rule like a flag value, "every" can be left out, "monday" or "2 weeks until 31/12/24"
+pub fn parse_rule(rule: &str) -> anyhow::Result<Recurrence> {
+	let mut words: Vec<&str> = rule.split_whitespace().collect();
+	if words.first().is_none_or(|word| !word.eq_ignore_ascii_case("every")) {
+		words.insert(0, "every");
+	}
+
+	match parse(&words, chrono::Local::now().date_naive()) {
+		Some((recurrence, consumed)) if consumed == words.len() => Ok(recurrence),
+		_ => Err(anyhow::format_err!("Could not read '{}' as a recurrence, use for example 'day', 'monday and friday', '2 weeks' or 'month until 31/12/24'", rule))
+	}
+}
+
+// "monday", "mon and fri" or "mon, wed and fri"
+fn parse_weekdays(words: &[String]) -> Option<(Vec<Weekday>, usize)> {
+	let mut weekdays = Vec::new();
+	let mut idx = 0;
+	while let Some(weekday) = words.get(idx).and_then(|word| date::parse_weekday(word)) {
+		if !weekdays.contains(&weekday) {
+			weekdays.push(weekday);
+		}
+		idx += 1;
+
+		match words.get(idx).map(|word| word.as_str()) {
+			Some("and") if words.get(idx + 1).and_then(|word| date::parse_weekday(word)).is_some() => idx += 1,
+			_ => ()
+		}
+	}
+
+	// Kept in the order of the week, so "fri and mon" and "mon and fri" are the same
+	weekdays.sort_by_key(|weekday| weekday.num_days_from_monday());
+	(!weekdays.is_empty()).then_some((weekdays, idx))
+}
+
+fn parse_count(word: &str) -> Option<u32> {
+	let count = match word {
+		"two" => 2,
+		"three" => 3,
+		"four" => 4,
+		"five" => 5,
+		"six" => 6,
+		_ => word.parse().ok()?
+	};
+
+	(count > 0).then_some(count)
+}
+
+// Written so that parse reads it back, e.g. "every 2 weeks on monday and friday until 2024-12-31"
+impl std::fmt::Display for Recurrence {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let unit = match self.unit {
+			Unit::Day => "day",
+			Unit::Week => "week",
+			Unit::Month => "month"
+		};
+		let weekdays: Vec<String> = self.weekdays.iter().map(|weekday| format_weekday(*weekday).to_string()).collect();
+		let weekdays = match weekdays.split_last() {
+			Some((last, [])) => last.clone(),
+			Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+			None => String::new()
+		};
+
+		match (self.every, weekdays.is_empty()) {
+			(1, false) if self.weekdays == WEEKDAYS => write!(f, "every weekday")?,
+			(1, true) => write!(f, "every {}", unit)?,
+			(1, false) => write!(f, "every {}", weekdays)?,
+			(every, true) => write!(f, "every {} {}s", every, unit)?,
+			(every, false) => write!(f, "every {} {}s on {}", every, unit, weekdays)?
+		}
+
+		if let Some(until) = self.until {
+			write!(f, " until {}", until.format("%Y-%m-%d"))?;
+		}
+
+		Ok(())
+	}
+}
+
+fn days_in_month(day: NaiveDate) -> u32 {
+	let first = day.with_day(1).unwrap();
+	first.checked_add_months(chrono::Months::new(1)).map_or(31, |next| (next - first).num_days() as u32)
+}
+
+fn format_weekday(weekday: Weekday) -> &'static str {
+	match weekday {
+		Weekday::Mon => "monday",
+		Weekday::Tue => "tuesday",
+		Weekday::Wed => "wednesday",
+		Weekday::Thu => "thursday",
+		Weekday::Fri => "friday",
+		Weekday::Sat => "saturday",
+		Weekday::Sun => "sunday"
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+		NaiveDate::from_ymd_opt(year, month, day).unwrap()
+	}
+
+	fn rule(rule: &str) -> Recurrence {
+		let words: Vec<&str> = rule.split_whitespace().collect();
+		let (recurrence, consumed) = parse(&words, day(2025, 1, 6)).unwrap();
+		assert_eq!(consumed, words.len(), "{}", rule);
+		recurrence
+	}
+
+	// The occurrences after the day, until the recurrence ends or the count is reached
+	fn occurrences(recurrence: &Recurrence, mut from: NaiveDate, count: usize) -> Vec<NaiveDate> {
+		let mut days = Vec::new();
+		while days.len() < count {
+			match recurrence.next_after(from) {
+				Some(next) => {
+					days.push(next);
+					from = next;
+				},
+				None => break
+			}
+		}
+		days
+	}
+
+	#[test]
+	fn steps_by_days_weeks_and_months() {
+		assert_eq!(occurrences(&rule("every day"), day(2025, 1, 30), 3), vec![day(2025, 1, 31), day(2025, 2, 1), day(2025, 2, 2)]);
+		assert_eq!(occurrences(&rule("every 3 days"), day(2025, 1, 1), 2), vec![day(2025, 1, 4), day(2025, 1, 7)]);
+		assert_eq!(occurrences(&rule("every other week"), day(2025, 1, 6), 2), vec![day(2025, 1, 20), day(2025, 2, 3)]);
+		assert_eq!(occurrences(&rule("every month"), day(2025, 1, 15), 2), vec![day(2025, 2, 15), day(2025, 3, 15)]);
+	}
+
+	#[test]
+	fn months_stay_on_the_first_day_of_the_month() {
+		let recurrence = rule("every month").anchored_on(day(2027, 1, 31));
+		assert_eq!(occurrences(&recurrence, day(2027, 1, 31), 4), vec![day(2027, 2, 28), day(2027, 3, 31), day(2027, 4, 30), day(2027, 5, 31)]);
+
+		let leap = rule("every 12 months").anchored_on(day(2024, 2, 29));
+		assert_eq!(occurrences(&leap, day(2024, 2, 29), 4), vec![day(2025, 2, 28), day(2026, 2, 28), day(2027, 2, 28), day(2028, 2, 29)]);
+
+		// Without a day of the month the clamped day is kept, which is why it is anchored on the first occurrence
+		assert_eq!(occurrences(&rule("every month"), day(2027, 2, 28), 1), vec![day(2027, 3, 28)]);
+	}
+
+	#[test]
+	fn anchor_is_only_set_once_and_only_for_months() {
+		let anchored = rule("every month").anchored_on(day(2027, 1, 31)).anchored_on(day(2027, 2, 28));
+		assert_eq!(anchored.day_of_month, Some(31));
+		assert_eq!(rule("every week").anchored_on(day(2027, 1, 31)).day_of_month, None);
+	}
+
+	#[test]
+	fn weekday_lists_step_through_the_week() {
+		// 6 January 2025 is a monday
+		let recurrence = rule("every mon, wed and fri");
+		assert_eq!(recurrence.weekdays, vec![Weekday::Mon, Weekday::Wed, Weekday::Fri]);
+		assert_eq!(occurrences(&recurrence, day(2025, 1, 6), 4), vec![day(2025, 1, 8), day(2025, 1, 10), day(2025, 1, 13), day(2025, 1, 15)]);
+
+		let weekdays = rule("every weekday");
+		assert_eq!(occurrences(&weekdays, day(2025, 1, 9), 3), vec![day(2025, 1, 10), day(2025, 1, 13), day(2025, 1, 14)]);
+
+		let every_other = rule("every 2 weeks on monday and friday");
+		assert_eq!(occurrences(&every_other, day(2025, 1, 6), 3), vec![day(2025, 1, 10), day(2025, 1, 20), day(2025, 1, 24)]);
+
+		assert_eq!(weekdays.first_on_or_after(day(2025, 1, 11)), Some(day(2025, 1, 13)));
+	}
+
+	#[test]
+	fn until_ends_the_recurrence() {
+		let recurrence = rule("every day until 2025-01-08");
+		assert_eq!(occurrences(&recurrence, day(2025, 1, 6), 5), vec![day(2025, 1, 7), day(2025, 1, 8)]);
+		assert_eq!(recurrence.first_on_or_after(day(2025, 1, 9)), None);
+
+		// A relative end is read from the given today, 6 January 2025
+		assert_eq!(rule("every day until friday").until, Some(day(2025, 1, 10)));
+	}
+
+	#[test]
+	fn missed_occurrences_are_skipped() {
+		let recurrence = rule("every day");
+		assert_eq!(recurrence.next_after_skipping(day(2025, 1, 1), day(2025, 1, 6)), Some(day(2025, 1, 7)));
+		assert_eq!(recurrence.next_after_skipping(day(2025, 1, 10), day(2025, 1, 6)), Some(day(2025, 1, 11)));
+	}
+
+	#[test]
+	fn every_zero_is_rejected() {
+		assert!(parse(&["every", "0", "days"], day(2025, 1, 6)).is_none());
+		assert!(serde_json::from_str::<Recurrence>(r#"{"every": 0, "unit": "day"}"#).is_err());
+
+		let zero = Recurrence{every: 0, unit: Unit::Day, weekdays: Vec::new(), until: None, day_of_month: None};
+		assert_eq!(zero.next_after(day(2025, 1, 6)), None);
+		assert_eq!(zero.next_after_skipping(day(2025, 1, 1), day(2025, 1, 6)), None);
+	}
+
+	#[test]
+	fn display_reads_back_the_same() {
+		for text in ["every day", "every 3 days", "every weekday", "every monday and friday", "every 2 weeks on monday", "every month until 2025-12-31"] {
+			assert_eq!(rule(text).to_string(), text);
+			assert!(rule(text) == rule(&rule(text).to_string()));
+		}
+	}
+}
@@ -0,0 +1,24 @@
+use colored::Colorize;
+
+use super::{data::Reminder, parse, utils};
+
+pub fn execute(free_text: &[String]) -> anyhow::Result<()> {
+	let parsed = parse::parse(free_text, chrono::Local::now())?;
+	let reminder = utils::update_storage(|data| {
+		let reminder = Reminder{
+			id: data.next_id.take(),
+			timestamp: chrono::Utc::now(),
+			due: parsed.due.with_timezone(&chrono::Utc),
+			message: parsed.message,
+			notified: None,
+			recurrence: parsed.recurrence
+		};
+		data.reminders.push(reminder.clone());
+		Ok(reminder)
+	})?;
+
+	println!("⏰ {}", "Reminder set!".green());
+	println!("{}", utils::format_listed_reminder(&reminder, chrono::Utc::now()));
+
+	Ok(())
+}
@@ -0,0 +1,31 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::utils;
+
+#[derive(Args)]
+pub struct Command {
+	/// Id of the reminder, like 3 or #3, or "last" for the reminder that was delivered last.
+	/// A reminder is picked interactively if not given
+	id: Option<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let id = match &command.id {
+		Some(id) => utils::parse_id(id)?,
+		None => match utils::pick("cancel")? {
+			Some(id) => id,
+			None => return Ok(())
+		}
+	};
+
+	let reminder = utils::update_reminders(|reminders| {
+		let index = utils::find_reminder(reminders, id)?;
+		Ok(reminders.remove(index))
+	})?;
+
+	println!("🗑️ {}", "Reminder cancelled".green());
+	println!("{}", utils::format_reminder(&reminder, true));
+
+	Ok(())
+}
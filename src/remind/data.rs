@@ -0,0 +1,32 @@
+use crate::{ids::NextId, recurrence::Recurrence};
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+pub struct ReminderStorage {
+	// The id given to the next reminder, ids are never reused
+	#[serde(default)]
+	pub next_id: NextId,
+	pub reminders: Vec<Reminder>
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct Reminder {
+	// 0 is only used for reminders written before ids existed
+	#[serde(default)]
+	pub id: u64,
+	pub timestamp: chrono::DateTime<chrono::Utc>,
+	pub due: chrono::DateTime<chrono::Utc>,
+	pub message: String,
+	// When the daemon last delivered the reminder, reminders that are due but not notified since are delivered when it runs again
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub notified: Option<chrono::DateTime<chrono::Utc>>,
+	// A recurring reminder is moved to its next occurrence when it is delivered
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub recurrence: Option<Recurrence>
+}
+
+impl Reminder {
+	// Whether the reminder is still to be delivered, a snoozed or recurring reminder is due again after it was notified
+	pub fn is_pending(&self) -> bool {
+		self.notified.is_none_or(|notified| notified < self.due)
+	}
+}
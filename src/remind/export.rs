@@ -0,0 +1,56 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::{data::Reminder, ics, utils};
+use crate::{storage, todo};
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum Format {
+	/// iCalendar (.ics), which calendar apps can import
+	Ics
+}
+
+#[derive(Args)]
+pub struct Command {
+	#[arg(short, long, value_enum, default_value_t = Format::Ics)]
+	format: Format,
+
+	/// Same as --format ics
+	#[arg(long, conflicts_with = "format")]
+	ics: bool,
+
+	/// Also export the reminders that have been delivered
+	#[arg(short, long)]
+	all: bool,
+
+	/// Leave out the todos that are due, they are exported as calendar todos otherwise
+	#[arg(long)]
+	no_todos: bool,
+
+	/// The file to write to, the calendar is printed if not given
+	#[arg(short, long)]
+	output: Option<std::path::PathBuf>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let reminders: Vec<Reminder> = utils::get_reminders()?
+		.into_iter()
+		.filter(|reminder| command.all || reminder.is_pending())
+		.collect();
+	let todos = if command.no_todos { Vec::new() } else { todo::get_due_todos()? };
+
+	let format = if command.ics { Format::Ics } else { command.format };
+	let content = match format {
+		Format::Ics => ics::write(&reminders, &todos, chrono::Utc::now())
+	};
+
+	match &command.output {
+		Some(path) => {
+			storage::write(path, &content)?;
+			println!("📤 {}", format!("Exported {} reminder(s) and {} todo(s) to {}", reminders.len(), todos.len(), path.display()).green());
+		},
+		None => print!("{}", content)
+	}
+
+	Ok(())
+}
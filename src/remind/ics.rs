@@ -0,0 +1,659 @@
+/*
+	Reading and writing iCalendar (.ics) as described in RFC 5545, so reminders show up in calendar apps.
+
+	Reminders are written as a VEVENT with an alarm at the time they are due, todos that are due as a VTODO and recurrences as an RRULE.
+	Recurring reminders are written in local time without a timezone, so "every monday at 9" stays at 9 when the clocks change,
+	the other reminders are written in UTC.
+	When reading, the VEVENTs and the VTODOs that aren't done both become reminders. Days without a time are at 9:00, like "on monday".
+	Times with a TZID are converted with the VTIMEZONE of that TZID in the file, which calendar apps write along with the times.
+	hey has no timezone database, so a time in another timezone without a VTIMEZONE can only be read if it is UTC or the local timezone.
+*/
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+
+use super::data::Reminder;
+use crate::{date, recurrence::{Recurrence, Unit}, todo::{Priority, Todo}};
+
+struct Property {
+	name: String,
+	params: Vec<(String, String)>,
+	value: String
+}
+
+impl Property {
+	fn param(&self, name: &str) -> Option<&str> {
+		self.params.iter().find(|(param, _)| param.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+	}
+}
+
+// A VTIMEZONE, the offsets from UTC of a TZID and the times they start to be used
+struct Timezone {
+	id: String,
+	observances: Vec<Observance>
+}
+
+// A STANDARD or DAYLIGHT part of a VTIMEZONE, the offset it switches to at its start and then once a year with its RRULE
+struct Observance {
+	start: NaiveDateTime,
+	offset_from: i32,
+	offset_to: i32,
+	rrule: Option<String>,
+	dates: Vec<NaiveDateTime>
+}
+
+impl Timezone {
+	// The offset used at the local time is the one of the switch that came last before it
+	fn to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+		let offset = self.observances.iter()
+			.flat_map(|observance| observance.onsets(local.year()).into_iter().map(|onset| (onset, observance.offset_to)))
+			.filter(|(onset, _)| *onset <= local)
+			.max_by_key(|(onset, _)| *onset)
+			.map(|(_, offset)| offset)
+			.or_else(|| self.observances.iter().min_by_key(|observance| observance.start).map(|observance| observance.offset_from))?;
+
+		Some((local - chrono::Duration::seconds(offset.into())).and_utc())
+	}
+}
+
+impl Observance {
+	// The local times the offset starts to be used, with the yearly ones of the given year and the year before
+	fn onsets(&self, year: i32) -> Vec<NaiveDateTime> {
+		let mut onsets = vec![self.start];
+		onsets.extend(&self.dates);
+		if let Some(rrule) = &self.rrule {
+			onsets.extend([year - 1, year].into_iter().filter_map(|year| yearly_onset(rrule, self.start, year)));
+		}
+
+		onsets
+	}
+}
+
+pub fn write(reminders: &[Reminder], todos: &[Todo], now: DateTime<Utc>) -> String {
+	let mut lines = vec![
+		"BEGIN:VCALENDAR".to_string(),
+		"VERSION:2.0".to_string(),
+		"PRODID:-//hey//hey-cli//EN".to_string()
+	];
+	for reminder in reminders {
+		lines.extend(write_event(reminder, now));
+	}
+	for todo in todos {
+		lines.extend(write_todo(todo, now));
+	}
+	lines.push("END:VCALENDAR".to_string());
+
+	lines.iter().map(|line| format!("{}\r\n", fold(line))).collect()
+}
+
+fn write_event(reminder: &Reminder, now: DateTime<Utc>) -> Vec<String> {
+	let start = match reminder.recurrence {
+		Some(_) => reminder.due.with_timezone(&Local).format("%Y%m%dT%H%M%S").to_string(),
+		None => format_utc(reminder.due)
+	};
+
+	let mut lines = vec![
+		"BEGIN:VEVENT".to_string(),
+		format!("UID:reminder-{}-{}@hey", reminder.id, reminder.timestamp.timestamp()),
+		format!("DTSTAMP:{}", format_utc(now)),
+		format!("DTSTART:{}", start),
+		format!("SUMMARY:{}", escape(&reminder.message))
+	];
+	lines.extend(reminder.recurrence.as_ref().map(|recurrence| write_rrule(recurrence, reminder.due.with_timezone(&Local).date_naive(), false)));
+	lines.extend([
+		"BEGIN:VALARM".to_string(),
+		"ACTION:DISPLAY".to_string(),
+		format!("DESCRIPTION:{}", escape(&reminder.message)),
+		"TRIGGER:PT0S".to_string(),
+		"END:VALARM".to_string(),
+		"END:VEVENT".to_string()
+	]);
+
+	lines
+}
+
+fn write_todo(todo: &Todo, now: DateTime<Utc>) -> Vec<String> {
+	let mut lines = vec![
+		"BEGIN:VTODO".to_string(),
+		format!("UID:todo-{}-{}@hey", todo.id, todo.timestamp.timestamp()),
+		format!("DTSTAMP:{}", format_utc(now)),
+		format!("CREATED:{}", format_utc(todo.timestamp)),
+		format!("SUMMARY:{}", escape(&todo.task))
+	];
+
+	if let Some(due) = todo.due {
+		// A recurrence is counted from DTSTART, so it's given as well
+		if todo.recurrence.is_some() {
+			lines.push(format!("DTSTART;VALUE=DATE:{}", due.format("%Y%m%d")));
+		}
+		lines.push(format!("DUE;VALUE=DATE:{}", due.format("%Y%m%d")));
+	}
+
+	// 1 is the highest priority and 9 the lowest
+	lines.extend(todo.priority.map(|priority| match priority {
+		Priority::High => "PRIORITY:1".to_string(),
+		Priority::Medium => "PRIORITY:5".to_string(),
+		Priority::Low => "PRIORITY:9".to_string()
+	}));
+
+	if !todo.tags.is_empty() {
+		lines.push(format!("CATEGORIES:{}", todo.tags.iter().map(|tag| escape(tag)).collect::<Vec<_>>().join(",")));
+	}
+	lines.extend(todo.recurrence.as_ref().zip(todo.due).map(|(recurrence, due)| write_rrule(recurrence, due, true)));
+	lines.push("STATUS:NEEDS-ACTION".to_string());
+	lines.push("END:VTODO".to_string());
+
+	lines
+}
+
+/*
+	UNTIL has to be a day when the start is a day, and a time without a timezone when the start is one.
+	Calendars skip the months that don't have the day of a monthly recurrence, hey moves it to the last day of the month.
+	"The last of the 28th to the 31st" is how that is written, BYMONTHDAY=28,29,30,31;BYSETPOS=-1.
+*/
+fn write_rrule(recurrence: &Recurrence, start: NaiveDate, days: bool) -> String {
+	let freq = match recurrence.unit {
+		Unit::Day => "DAILY",
+		Unit::Week => "WEEKLY",
+		Unit::Month => "MONTHLY"
+	};
+
+	let mut parts = vec![format!("FREQ={}", freq)];
+	if recurrence.every > 1 {
+		parts.push(format!("INTERVAL={}", recurrence.every));
+	}
+	if recurrence.unit == Unit::Month {
+		match recurrence.day_of_month.unwrap_or(start.day()) {
+			day if day > 28 => parts.push(format!("BYMONTHDAY={};BYSETPOS=-1", (28..=day).map(|day| day.to_string()).collect::<Vec<_>>().join(","))),
+			day if day != start.day() => parts.push(format!("BYMONTHDAY={}", day)),
+			_ => ()
+		}
+	}
+	if !recurrence.weekdays.is_empty() {
+		parts.push(format!("BYDAY={}", recurrence.weekdays.iter().map(|weekday| weekday_code(*weekday)).collect::<Vec<_>>().join(",")));
+	}
+	if let Some(until) = recurrence.until {
+		match days {
+			true => parts.push(format!("UNTIL={}", until.format("%Y%m%d"))),
+			false => parts.push(format!("UNTIL={}T235959", until.format("%Y%m%d")))
+		}
+	}
+
+	format!("RRULE:{}", parts.join(";"))
+}
+
+/*
+	The reminders in the content, an entry that can't be read is an error of its own so the others can still be imported.
+	The ids and the times they were set are given when they are stored.
+*/
+pub fn read(content: &str) -> anyhow::Result<Vec<anyhow::Result<Reminder>>> {
+	let properties: Vec<Property> = unfold(content).iter().filter_map(|line| split_property(line)).collect();
+	if !properties.iter().any(|property| property.name == "BEGIN" && property.value.eq_ignore_ascii_case("VCALENDAR")) {
+		return Err(anyhow::format_err!("This is not an iCalendar file, it has no BEGIN:VCALENDAR"));
+	}
+	let timezones = read_timezones(&properties);
+
+	let mut entries = Vec::new();
+	let mut component: Option<(String, Vec<&Property>)> = None;
+	// Components inside an entry, like VALARM, have properties of their own that are not read
+	let mut nested = 0;
+
+	for property in &properties {
+		let value = property.value.to_uppercase();
+		match (property.name.as_str(), value.as_str(), &mut component) {
+			("BEGIN", "VEVENT" | "VTODO", None) => component = Some((value.clone(), Vec::new())),
+			("BEGIN", _, Some(_)) => nested += 1,
+			("END", _, Some(_)) if nested > 0 => nested -= 1,
+			("END", "VEVENT" | "VTODO", Some((kind, entry))) => {
+				entries.push(read_entry(kind, entry, &timezones));
+				component = None;
+			},
+			(_, _, Some((_, entry))) if nested == 0 => entry.push(property),
+			_ => ()
+		}
+	}
+
+	Ok(entries)
+}
+
+fn read_entry(kind: &str, entry: &[&Property], timezones: &[Timezone]) -> anyhow::Result<Reminder> {
+	let get = |name: &str| entry.iter().find(|property| property.name == name).copied();
+
+	let message = get("SUMMARY")
+		.map(|summary| unescape(&summary.value))
+		.filter(|summary| !summary.trim().is_empty())
+		.ok_or(anyhow::format_err!("A {} has no SUMMARY", kind))?;
+
+	if get("STATUS").is_some_and(|status| ["COMPLETED", "CANCELLED"].contains(&status.value.to_uppercase().as_str())) {
+		return Err(anyhow::format_err!("'{}' is already done", message));
+	}
+
+	// A todo is due at DUE, an event at its start
+	let time = match kind {
+		"VTODO" => get("DUE").or(get("DTSTART")),
+		_ => get("DTSTART")
+	}.ok_or(anyhow::format_err!("'{}' has no time it's due", message))?;
+	let due = match time.param("TZID").filter(|tzid| !is_utc(tzid)) {
+		Some(tzid) => match timezones.iter().find(|timezone| timezone.id == tzid) {
+			Some(timezone) => read_zoned_time(time, timezone),
+			None => {
+				check_timezone(tzid).map_err(|err| anyhow::format_err!("'{}' {}", message, err))?;
+				read_time(time)
+			}
+		},
+		None => read_time(time)
+	}.ok_or(anyhow::format_err!("'{}' has a time that can't be read, {}", message, time.value))?;
+
+	let recurrence = match get("RRULE") {
+		Some(rrule) => Some(read_rrule(&rrule.value, due.with_timezone(&Local).date_naive()).map_err(|err| anyhow::format_err!("'{}' {}", message, err))?),
+		None => None
+	};
+
+	Ok(Reminder {
+		id: 0,
+		timestamp: Utc::now(),
+		due,
+		message,
+		notified: None,
+		recurrence
+	})
+}
+
+// "20241018T090000Z" in UTC, "20241018T090000" in local time and "20241018" at the time of a day without a time
+fn read_time(property: &Property) -> Option<DateTime<Utc>> {
+	let value = property.value.trim();
+	if property.param("VALUE").is_some_and(|value| value.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+		let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+		let time = NaiveTime::from_hms_opt(date::DEFAULT_HOUR, 0, 0)?;
+		return date::to_local(day, time).ok().map(|due| due.with_timezone(&Utc));
+	}
+
+	match value.strip_suffix('Z').or(property.param("TZID").filter(|tzid| is_utc(tzid)).map(|_| value)) {
+		Some(utc) => Some(NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?.and_utc()),
+		None => {
+			let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+			date::to_local(local.date(), local.time()).ok().map(|due| due.with_timezone(&Utc))
+		}
+	}
+}
+
+// A time in a timezone of the file, a day without a time is read the same as without the timezone
+fn read_zoned_time(property: &Property, timezone: &Timezone) -> Option<DateTime<Utc>> {
+	match NaiveDateTime::parse_from_str(property.value.trim(), "%Y%m%dT%H%M%S") {
+		Ok(local) => timezone.to_utc(local),
+		Err(_) => read_time(property)
+	}
+}
+
+// A time with a TZID the file has no VTIMEZONE for can be read when the TZID is the local timezone, "/vendor/Europe/London" matches Europe/London
+fn check_timezone(tzid: &str) -> anyhow::Result<()> {
+	match local_timezone() {
+		Some(local) if tzid.eq_ignore_ascii_case(&local) || tzid.to_lowercase().ends_with(&format!("/{}", local.to_lowercase())) => Ok(()),
+		Some(local) if is_utc(&local) => Err(anyhow::format_err!("is in the timezone {}, which the file has no VTIMEZONE for, only times in UTC can be read without one", tzid)),
+		Some(local) => Err(anyhow::format_err!("is in the timezone {}, which the file has no VTIMEZONE for, only times in {} or UTC can be read without one", tzid, local)),
+		None => Err(anyhow::format_err!("is in the timezone {}, which the file has no VTIMEZONE for and the local timezone is not known to compare it with", tzid))
+	}
+}
+
+fn is_utc(tzid: &str) -> bool {
+	["UTC", "Etc/UTC", "GMT", "Etc/GMT", "Z"].iter().any(|utc| tzid.eq_ignore_ascii_case(utc))
+}
+
+// The name of the local timezone from $TZ, or the zoneinfo file /etc/localtime links to
+fn local_timezone() -> Option<String> {
+	if let Some(tz) = std::env::var("TZ").ok().map(|tz| tz.trim_start_matches(':').to_string()).filter(|tz| !tz.is_empty()) {
+		return Some(tz);
+	}
+
+	let target = std::fs::read_link("/etc/localtime").ok()?;
+	let target = target.to_str()?;
+	target.split_once("zoneinfo/").map(|(_, name)| name.to_string())
+}
+
+// The VTIMEZONEs in the file, a STANDARD or DAYLIGHT part that can't be read is left out
+fn read_timezones(properties: &[Property]) -> Vec<Timezone> {
+	let mut timezones = Vec::new();
+	let mut timezone: Option<Timezone> = None;
+	let mut observance: Option<Vec<&Property>> = None;
+
+	for property in properties {
+		let value = property.value.to_uppercase();
+		match (property.name.as_str(), value.as_str(), &mut timezone) {
+			("BEGIN", "VTIMEZONE", _) => timezone = Some(Timezone{id: String::new(), observances: Vec::new()}),
+			("END", "VTIMEZONE", _) => timezones.extend(timezone.take()),
+			("BEGIN", "STANDARD" | "DAYLIGHT", Some(_)) => observance = Some(Vec::new()),
+			("END", "STANDARD" | "DAYLIGHT", Some(timezone)) => timezone.observances.extend(observance.take().and_then(|part| read_observance(&part))),
+			("TZID", _, Some(timezone)) if observance.is_none() => timezone.id = property.value.clone(),
+			_ => observance.iter_mut().for_each(|part| part.push(property))
+		}
+	}
+
+	timezones
+}
+
+fn read_observance(part: &[&Property]) -> Option<Observance> {
+	let get = |name: &str| part.iter().find(|property| property.name == name).map(|property| property.value.trim());
+	let read_local = |value: &str| NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok();
+
+	Some(Observance {
+		start: read_local(get("DTSTART")?)?,
+		offset_from: read_offset(get("TZOFFSETFROM")?)?,
+		offset_to: read_offset(get("TZOFFSETTO")?)?,
+		rrule: get("RRULE").map(str::to_string),
+		dates: part.iter()
+			.filter(|property| property.name == "RDATE")
+			.flat_map(|property| property.value.split(','))
+			.filter_map(|value| read_local(value.trim()))
+			.collect()
+	})
+}
+
+// "+0100" or "-053000" in seconds
+fn read_offset(offset: &str) -> Option<i32> {
+	let (sign, digits) = match offset.split_at_checked(1)? {
+		("+", digits) => (1, digits),
+		("-", digits) => (-1, digits),
+		_ => return None
+	};
+	if !matches!(digits.len(), 4 | 6) || !digits.chars().all(|c| c.is_ascii_digit()) {
+		return None;
+	}
+
+	let part = |range: std::ops::Range<usize>| digits.get(range).map_or(Some(0), |part| part.parse::<i32>().ok());
+	Some(sign * (part(0..2)? * 3600 + part(2..4)? * 60 + part(4..6)?))
+}
+
+/*
+	The time in the year a yearly RRULE of a VTIMEZONE switches, the ones calendar apps write look like
+	"FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU", the last sunday of march. The time of day is the one of the start.
+*/
+fn yearly_onset(rrule: &str, start: NaiveDateTime, year: i32) -> Option<NaiveDateTime> {
+	let mut month = start.month();
+	let mut weekday = None;
+	let mut month_day = None;
+	let mut until = None;
+
+	for part in rrule.split(';').filter(|part| !part.is_empty()) {
+		let (key, value) = part.split_once('=')?;
+		match key.to_uppercase().as_str() {
+			"FREQ" if value.eq_ignore_ascii_case("YEARLY") => (),
+			"INTERVAL" if value == "1" => (),
+			"WKST" => (),
+			"BYMONTH" => month = value.parse().ok()?,
+			"BYMONTHDAY" => month_day = Some(value.parse::<u32>().ok()?),
+			// "-1SU" is the last sunday of the month and "2SU" the second
+			"BYDAY" => {
+				let (position, code) = value.split_at_checked(value.len().checked_sub(2)?)?;
+				weekday = Some((position.trim_start_matches('+').parse::<i32>().ok()?, parse_weekday_code(code)?));
+			},
+			"UNTIL" => until = Some(NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?),
+			_ => return None
+		}
+	}
+
+	let day = match weekday {
+		Some((position, weekday)) => nth_weekday(year, month, weekday, position)?,
+		None => NaiveDate::from_ymd_opt(year, month, month_day.unwrap_or(start.day()))?
+	};
+	let onset = day.and_time(start.time());
+	(onset >= start && until.is_none_or(|until| day <= until)).then_some(onset)
+}
+
+// The second monday of the month for 2, the last one for -1
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, position: i32) -> Option<NaiveDate> {
+	match position {
+		0 => return None,
+		1.. => return NaiveDate::from_weekday_of_month_opt(year, month, weekday, u8::try_from(position).ok()?),
+		_ => ()
+	}
+
+	let next_month = if month == 12 { NaiveDate::from_ymd_opt(year + 1, 1, 1) } else { NaiveDate::from_ymd_opt(year, month + 1, 1) }?;
+	let last = next_month.pred_opt()?;
+	let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+	let day = last - chrono::Duration::days(i64::from(back) + 7 * i64::from(-position - 1));
+	(day.month() == month).then_some(day)
+}
+
+/*
+	Reads the RRULEs that hey can repeat: daily, weekly on given days and monthly, with an interval, UNTIL or COUNT.
+	Yearly is read as every 12 months and daily on given days, how some apps write "every weekday", as weekly.
+	A monthly day is read from BYMONTHDAY, with the last day of the month as -1 or as the last of a few days with BYSETPOS=-1.
+*/
+fn read_rrule(rrule: &str, start: NaiveDate) -> anyhow::Result<Recurrence> {
+	let unsupported = || anyhow::format_err!("repeats as {}, which hey can't repeat", rrule);
+	let mut recurrence = Recurrence{every: 1, unit: Unit::Day, weekdays: Vec::new(), until: None, day_of_month: None};
+	let mut months = 1;
+	let mut count = None;
+	let mut month_days: Vec<i32> = Vec::new();
+	let mut last_of_days = false;
+
+	for part in rrule.split(';').filter(|part| !part.is_empty()) {
+		let (key, value) = part.split_once('=').ok_or_else(unsupported)?;
+		match key.to_uppercase().as_str() {
+			"FREQ" => {
+				(recurrence.unit, months) = match value.to_uppercase().as_str() {
+					"DAILY" => (Unit::Day, 1),
+					"WEEKLY" => (Unit::Week, 1),
+					"MONTHLY" => (Unit::Month, 1),
+					"YEARLY" => (Unit::Month, 12),
+					_ => return Err(unsupported())
+				}
+			},
+			"INTERVAL" => recurrence.every = value.parse().ok().filter(|every| *every > 0).ok_or_else(unsupported)?,
+			// Days with a position, like "1MO" for the first monday of the month, are not supported
+			"BYDAY" => recurrence.weekdays = value.split(',').map(parse_weekday_code).collect::<Option<Vec<_>>>().ok_or_else(unsupported)?,
+			"UNTIL" => recurrence.until = Some(NaiveDate::parse_from_str(value.get(..8).ok_or_else(unsupported)?, "%Y%m%d").map_err(|_| unsupported())?),
+			"BYMONTHDAY" => month_days = value.split(',').map(|day| day.parse().ok().filter(|day: &i32| (1..=31).contains(day) || *day == -1)).collect::<Option<Vec<_>>>().ok_or_else(unsupported)?,
+			"BYSETPOS" if value == "-1" => last_of_days = true,
+			"COUNT" => count = Some(value.parse::<u32>().ok().filter(|count| *count > 0).ok_or_else(unsupported)?),
+			"WKST" => (),
+			_ => return Err(unsupported())
+		}
+	}
+
+	if recurrence.unit == Unit::Month {
+		recurrence.every = recurrence.every.checked_mul(months).ok_or_else(unsupported)?;
+	}
+
+	if recurrence.unit == Unit::Month {
+		recurrence.day_of_month = match (month_days.as_slice(), last_of_days) {
+			([], false) => Some(start.day()),
+			([-1], false) => Some(31),
+			([day], false) => Some(*day as u32),
+			// The 28th to the 31st, of which the last day a month has
+			(days, true) if days.iter().all(|day| *day >= 28) => days.iter().max().map(|day| *day as u32),
+			_ => return Err(unsupported())
+		};
+	} else if !month_days.is_empty() || last_of_days {
+		return Err(unsupported());
+	}
+
+	if !recurrence.weekdays.is_empty() {
+		match recurrence.unit {
+			Unit::Week => (),
+			Unit::Day if recurrence.every == 1 => recurrence.unit = Unit::Week,
+			_ => return Err(unsupported())
+		}
+		recurrence.weekdays.sort_by_key(|weekday| weekday.num_days_from_monday());
+		recurrence.weekdays.dedup();
+	}
+
+	// The count is kept as the day of the last occurrence
+	if let Some(count) = count {
+		let mut last = start;
+		for _ in 1..count {
+			last = recurrence.next_after(last).ok_or_else(unsupported)?;
+		}
+		recurrence.until = Some(last);
+	}
+
+	Ok(recurrence)
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+	match weekday {
+		Weekday::Mon => "MO",
+		Weekday::Tue => "TU",
+		Weekday::Wed => "WE",
+		Weekday::Thu => "TH",
+		Weekday::Fri => "FR",
+		Weekday::Sat => "SA",
+		Weekday::Sun => "SU"
+	}
+}
+
+fn parse_weekday_code(code: &str) -> Option<Weekday> {
+	[Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun]
+		.into_iter()
+		.find(|weekday| code.trim().eq_ignore_ascii_case(weekday_code(*weekday)))
+}
+
+fn format_utc(timestamp: DateTime<Utc>) -> String {
+	timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+	text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+	let mut unescaped = String::new();
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		match (c, chars.clone().next()) {
+			('\\', Some('n' | 'N')) => {
+				unescaped.push('\n');
+				chars.next();
+			},
+			('\\', Some(escaped)) => {
+				unescaped.push(escaped);
+				chars.next();
+			},
+			(c, _) => unescaped.push(c)
+		}
+	}
+
+	unescaped
+}
+
+// Lines are at most 75 bytes long, longer lines continue on the next line after a space
+fn fold(line: &str) -> String {
+	let mut folded = String::new();
+	let mut length = 0;
+	for c in line.chars() {
+		if length + c.len_utf8() > 75 {
+			folded += "\r\n ";
+			length = 1;
+		}
+		folded.push(c);
+		length += c.len_utf8();
+	}
+
+	folded
+}
+
+fn unfold(content: &str) -> Vec<String> {
+	let mut lines: Vec<String> = Vec::new();
+	for line in content.lines() {
+		match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+			(Some(continued), Some(last)) => last.push_str(continued),
+			_ => lines.push(line.to_string())
+		}
+	}
+
+	lines
+}
+
+// "DTSTART;TZID=Europe/London:20241018T090000" -> DTSTART, [(TZID, Europe/London)], 20241018T090000
+fn split_property(line: &str) -> Option<Property> {
+	// The value starts at the first colon that isn't in a quoted parameter value
+	let mut quoted = false;
+	let colon = line.char_indices().find(|(_, c)| {
+		if *c == '"' {
+			quoted = !quoted;
+		}
+		*c == ':' && !quoted
+	})?.0;
+
+	let (head, value) = (&line[..colon], &line[colon + 1..]);
+	let mut parts = head.split(';');
+	let name = parts.next()?.trim().to_uppercase();
+	let params = parts
+		.filter_map(|param| param.split_once('='))
+		.map(|(key, value)| (key.to_string(), value.trim_matches('"').to_string()))
+		.collect();
+
+	Some(Property{name, params, value: value.to_string()})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const BERLIN: &str = "BEGIN:VCALENDAR
+BEGIN:VTIMEZONE
+TZID:Europe/Berlin
+BEGIN:DAYLIGHT
+TZOFFSETFROM:+0100
+TZOFFSETTO:+0200
+DTSTART:19700329T020000
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU
+END:DAYLIGHT
+BEGIN:STANDARD
+TZOFFSETFROM:+0200
+TZOFFSETTO:+0100
+DTSTART:19701025T030000
+RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU
+END:STANDARD
+END:VTIMEZONE
+";
+
+	fn read_due(calendar: &str, start: &str) -> anyhow::Result<DateTime<Utc>> {
+		let content = format!("{}BEGIN:VEVENT\nSUMMARY:Call\nDTSTART;{}\nEND:VEVENT\nEND:VCALENDAR\n", calendar, start);
+		read(&content)?.pop().unwrap().map(|reminder| reminder.due)
+	}
+
+	fn utc(time: &str) -> DateTime<Utc> {
+		NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap().and_utc()
+	}
+
+	#[test]
+	fn times_are_converted_with_the_vtimezone() {
+		assert_eq!(read_due(BERLIN, "TZID=Europe/Berlin:20260115T090000").unwrap(), utc("2026-01-15 08:00"));
+		assert_eq!(read_due(BERLIN, "TZID=Europe/Berlin:20260715T090000").unwrap(), utc("2026-07-15 07:00"));
+		// The clocks change on the last sunday of march and of october
+		assert_eq!(read_due(BERLIN, "TZID=Europe/Berlin:20260328T120000").unwrap(), utc("2026-03-28 11:00"));
+		assert_eq!(read_due(BERLIN, "TZID=Europe/Berlin:20260329T120000").unwrap(), utc("2026-03-29 10:00"));
+		assert_eq!(read_due(BERLIN, "TZID=Europe/Berlin:20261025T120000").unwrap(), utc("2026-10-25 11:00"));
+	}
+
+	#[test]
+	fn timezone_without_yearly_changes_uses_its_offset() {
+		let calendar = "BEGIN:VCALENDAR\nBEGIN:VTIMEZONE\nTZID:India Standard Time\nBEGIN:STANDARD\nTZOFFSETFROM:+0530\nTZOFFSETTO:+0530\nDTSTART:16010101T000000\nEND:STANDARD\nEND:VTIMEZONE\n";
+		assert_eq!(read_due(calendar, "TZID=India Standard Time:20261018T090000").unwrap(), utc("2026-10-18 03:30"));
+	}
+
+	#[test]
+	fn timezone_without_vtimezone_is_an_error() {
+		assert!(read_due(BERLIN, "TZID=Mars/Olympus_Mons:20261018T090000").is_err());
+		assert_eq!(read_due(BERLIN, "TZID=UTC:20261018T090000").unwrap(), utc("2026-10-18 09:00"));
+	}
+
+	#[test]
+	fn weekdays_of_the_month() {
+		let day = |year, month, day| NaiveDate::from_ymd_opt(year, month, day);
+		assert_eq!(nth_weekday(2026, 3, Weekday::Sun, -1), day(2026, 3, 29));
+		assert_eq!(nth_weekday(2026, 3, Weekday::Sun, 2), day(2026, 3, 8));
+		assert_eq!(nth_weekday(2026, 12, Weekday::Thu, -1), day(2026, 12, 31));
+		assert_eq!(nth_weekday(2026, 2, Weekday::Mon, -5), None);
+		assert_eq!(nth_weekday(2026, 2, Weekday::Mon, 0), None);
+	}
+
+	#[test]
+	fn offsets() {
+		assert_eq!(read_offset("+0100"), Some(3600));
+		assert_eq!(read_offset("-0530"), Some(-19800));
+		assert_eq!(read_offset("+053045"), Some(19845));
+		assert_eq!(read_offset("0100"), None);
+		assert_eq!(read_offset("+1"), None);
+	}
+}
@@ -0,0 +1,76 @@
+use std::io::Read;
+
+use clap::Args;
+use colored::Colorize;
+
+use super::{data::Reminder, ics, utils};
+
+#[derive(Args)]
+pub struct Command {
+	/// The iCalendar (.ics) file to import, "-" reads from stdin.
+	/// Times in a timezone are converted with the VTIMEZONE of the file, the ones in a timezone it has none for are skipped unless it is UTC or the local timezone
+	file: std::path::PathBuf
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let content = if command.file.as_os_str() == "-" {
+		let mut content = String::new();
+		std::io::stdin().read_to_string(&mut content)?;
+		content
+	} else {
+		std::fs::read_to_string(&command.file).map_err(|err| anyhow::format_err!("Could not read {}: {}", command.file.display(), err))?
+	};
+
+	let mut reminders = Vec::new();
+	for entry in ics::read(&content)? {
+		match entry {
+			Ok(reminder) => reminders.push(reminder),
+			Err(err) => println!("Skipped: {}", err)
+		}
+	}
+
+	let (added, existing, passed) = import(reminders)?;
+	println!("📥 {}", format!("Imported {} reminder(s)", added).green());
+	if existing > 0 {
+		println!("Skipped {} reminder(s) that were already set", existing);
+	}
+	if passed > 0 {
+		println!("Skipped {} reminder(s) that have already passed", passed);
+	}
+
+	Ok(())
+}
+
+/*
+	Adds the reminders that are still to come, a recurring reminder that started in the past starts at its next occurrence.
+	A reminder is skipped if one with the same message is due at the same time, so importing the same file twice adds nothing.
+	Returns the amount of reminders added, skipped because they exist and skipped because they have passed.
+*/
+fn import(reminders: Vec<Reminder>) -> anyhow::Result<(usize, usize, usize)> {
+	let now = chrono::Utc::now();
+	utils::update_storage(|data| {
+		let (mut added, mut existing, mut passed) = (0, 0, 0);
+		for mut reminder in reminders {
+			if reminder.due <= now {
+				match reminder.recurrence.as_ref().and_then(|recurrence| super::next_due(reminder.due, recurrence, now)) {
+					Some(due) => reminder.due = due,
+					None => {
+						passed += 1;
+						continue;
+					}
+				}
+			}
+
+			if data.reminders.iter().any(|other| other.due == reminder.due && other.message.to_lowercase() == reminder.message.to_lowercase()) {
+				existing += 1;
+				continue;
+			}
+
+			reminder.id = data.next_id.take();
+			data.reminders.push(reminder);
+			added += 1;
+		}
+
+		Ok((added, existing, passed))
+	})
+}
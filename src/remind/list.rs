@@ -0,0 +1,29 @@
+use clap::Args;
+
+use super::{data::Reminder, utils};
+
+#[derive(Args)]
+pub struct Command {
+	/// Also show the reminders that have been delivered
+	#[arg(short, long)]
+	all: bool
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let now = chrono::Utc::now();
+	let mut reminders: Vec<Reminder> = utils::get_reminders()?
+		.into_iter()
+		.filter(|reminder| command.all || reminder.is_pending())
+		.collect();
+	reminders.sort_by_key(|reminder| reminder.due);
+
+	if reminders.is_empty() {
+		println!("📭 No reminders coming up");
+	}
+
+	for reminder in reminders {
+		println!("{}", utils::format_listed_reminder(&reminder, now));
+	}
+
+	Ok(())
+}
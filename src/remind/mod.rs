@@ -0,0 +1,140 @@
+
+// hey remind me to X at Y -> Tokenized: CLI: "hey", COMMAND: "remind", SUBJECT: "me", KEYWORD: "to", WORD: "X"..., KEYWORD: "at", TIME: "y"
+
+// Variations:
+// "hey remind me to X at Y"				CLI COMMAND SUBJECT TO CONTENT AT TIME
+// "hey remind us to X at Y"				CLI COMMAND SUBJECT TO CONTENT AT TIME
+// "hey remind me at Y to X"				CLI COMMAND SUBJECT AT TIME TO CONTENT
+// "hey remind me to X tomorrow at Y"		CLI COMMAND SUBJECT TO CONTENT RELATIVE_DAY AT TIME
+// "hey remind me to X in two weeks"		CLI COMMAND SUBJECT TO CONTENT IN NUMBER COUNTABLE_DAY
+// "hey remind me on monday at 5pm to X"	CLI COMMAND SUBJECT ON DAY AT TIME TO CONTENT
+// "hey remind me to X on monday"			CLI COMMAND SUBJECT TO CONTENT ON DAY
+// "hey remind me to X every monday at Y"	CLI COMMAND SUBJECT TO CONTENT EVERY DAY AT TIME
+
+// Variations where it doesn't start with "remind" (for free text option)
+// "hey in two weeks remind me to X"		CLI IN NUMBER COUNTABLE_DAY COMMAND SUBJECT TO CONTENT
+// "hey at 5pm remind me to X"				CLI AT TIME COMMAND SUBJECT TO CONTENT
+
+// "hey add to list to remind me to eat pasta"
+
+use clap::{Args, Subcommand};
+
+use crate::{date, recurrence::Recurrence};
+
+pub use data::Reminder;
+pub use utils::format_reminder;
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Command {
+	#[command(subcommand)]
+	command: Option<Commands>,
+
+	/// What to be reminded of and when, e.g. "me to water the plants at 5pm" or "us every monday to take out the bins"
+	free_text: Vec<String>
+}
+
+#[derive(Subcommand)]
+enum Commands {
+	/// Show the reminders that are coming up, with how long until they are due
+	List(list::Command),
+	/// Remove a reminder
+	Cancel(cancel::Command),
+	/// Deliver a reminder again later, in 10 minutes if no time is given
+	Snooze(snooze::Command),
+	/// Move a reminder to another time
+	Reschedule(reschedule::Command),
+	/// Write the reminders and the todos that are due as a calendar
+	Export(export::Command),
+	/// Add the events and todos of a calendar as reminders, reminders that already exist are skipped
+	Import(import::Command)
+}
+
+pub fn execute(input: Command) -> anyhow::Result<()> {
+	match input.command {
+		Some(Commands::List(input)) => list::execute(input),
+		Some(Commands::Cancel(input)) => cancel::execute(input),
+		Some(Commands::Snooze(input)) => snooze::execute(input),
+		Some(Commands::Reschedule(input)) => reschedule::execute(input),
+		Some(Commands::Export(input)) => export::execute(input),
+		Some(Commands::Import(input)) => import::execute(input),
+		None => add::execute(&input.free_text)
+	}
+}
+
+// Reminders that are due at the time and have not been delivered yet, the oldest first
+pub fn get_due_reminders(now: chrono::DateTime<chrono::Utc>) -> anyhow::Result<Vec<Reminder>> {
+	let mut due: Vec<Reminder> = utils::get_reminders()?
+		.into_iter()
+		.filter(|reminder| reminder.is_pending() && reminder.due <= now)
+		.collect();
+	due.sort_by_key(|reminder| reminder.due);
+
+	Ok(due)
+}
+
+// The first time a reminder that hasn't been delivered is due
+pub fn get_next_due() -> anyhow::Result<Option<chrono::DateTime<chrono::Utc>>> {
+	Ok(utils::get_reminders()?
+		.iter()
+		.filter(|reminder| reminder.is_pending())
+		.map(|reminder| reminder.due)
+		.min())
+}
+
+// A recurring reminder is moved to its next occurrence as well, until the recurrence ends
+pub fn mark_notified(reminder: &Reminder, now: chrono::DateTime<chrono::Utc>) -> anyhow::Result<()> {
+	utils::update_reminders(|reminders| {
+		// A reminder that was cancelled or rescheduled while it was delivered is left as it is
+		if let Some(stored) = reminders.iter_mut().find(|stored| stored.id == reminder.id && stored.due == reminder.due) {
+			stored.notified = Some(now);
+			advance(stored, now);
+		}
+		Ok(())
+	})
+}
+
+// Moves a recurring reminder to its first occurrence after the time, returns false if it has no occurrence left
+fn advance(reminder: &mut Reminder, after: chrono::DateTime<chrono::Utc>) -> bool {
+	let Some(recurrence) = &reminder.recurrence else {
+		return false;
+	};
+
+	let recurrence = recurrence.anchored_on(reminder.due.with_timezone(&chrono::Local).date_naive());
+	let next = next_due(reminder.due, &recurrence, after);
+	reminder.recurrence = Some(recurrence);
+	match next {
+		Some(due) => {
+			reminder.due = due;
+			true
+		},
+		None => false
+	}
+}
+
+// The first occurrence after now at the same time of the day, occurrences that were missed while the daemon wasn't running are skipped
+fn next_due(due: chrono::DateTime<chrono::Utc>, recurrence: &Recurrence, now: chrono::DateTime<chrono::Utc>) -> Option<chrono::DateTime<chrono::Utc>> {
+	let due = due.with_timezone(&chrono::Local);
+	let mut day = recurrence.next_after(due.date_naive())?;
+	loop {
+		// A time that doesn't exist on the day because of daylight saving is skipped as well
+		if let Ok(next) = date::to_local(day, due.time()) {
+			if next > now {
+				return Some(next.with_timezone(&chrono::Utc));
+			}
+		}
+		day = recurrence.next_after(day)?;
+	}
+}
+
+mod add;
+mod list;
+mod cancel;
+mod snooze;
+mod reschedule;
+mod export;
+mod import;
+mod ics;
+mod data;
+mod parse;
+mod utils;
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Local};
+
+use crate::{date::When, recurrence::Recurrence};
+
+pub struct ParsedReminder {
+	pub message: String,
+	pub due: DateTime<Local>,
+	pub recurrence: Option<Recurrence>
+}
+
+const SUBJECTS: [&str; 2] = ["me", "us"];
+
+/*
+	Parses the words after "hey remind", e.g. ["me", "to", "X", "at", "5pm"], into a message and a due time.
+	Time clauses ("at 5pm", "on monday", "tomorrow", "in two weeks", "every friday") can be placed both before and after the content.
+	A clause that does not parse as a time, like "at the office", is kept as part of the content.
+*/
+pub fn parse(original_words: &[String], now: DateTime<Local>) -> anyhow::Result<ParsedReminder> {
+	// Clauses are matched in lowercase while the content keeps the casing it was written with
+	let words: Vec<String> = original_words.iter().map(|w| w.to_lowercase()).collect();
+	let mut when = When::new(now);
+	let mut content: Vec<&str> = Vec::new();
+	let mut content_ended = false;
+
+	let mut idx = 0;
+	if words.first().is_some_and(|w| SUBJECTS.contains(&w.as_str())) {
+		idx += 1;
+	}
+
+	while idx < words.len() {
+		if let Some(consumed) = when.read_clause(&words[idx..]) {
+			idx += consumed;
+			content_ended = !content.is_empty();
+			continue;
+		}
+
+		if content_ended {
+			return Err(anyhow::format_err!("Did not understand '{}' after the reminder time", words[idx]));
+		}
+
+		// The content is introduced by "to", as in "remind me to X"
+		if !(content.is_empty() && words[idx] == "to") {
+			content.push(&original_words[idx]);
+		}
+		idx += 1;
+	}
+
+	if content.is_empty() {
+		return Err(anyhow::format_err!("Nothing to be reminded about, try 'hey remind me to X at Y'"));
+	}
+
+	let recurrence = when.recurrence().cloned();
+	Ok(ParsedReminder {
+		message: content.join(" "),
+		due: when.resolve()?,
+		recurrence
+	})
+}
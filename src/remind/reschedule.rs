@@ -0,0 +1,44 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::utils;
+use crate::date::When;
+
+#[derive(Args)]
+pub struct Command {
+	/// Id of the reminder, like 3 or #3, or "last" for the reminder that was delivered last
+	id: String,
+
+	/// The new time, e.g. "tomorrow at 5pm", "on friday" or "in two hours". "every monday" changes how often it recurs as well
+	#[arg(required = true)]
+	time: Vec<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let id = utils::parse_id(&command.id)?;
+
+	// "reschedule that to friday"
+	let mut words: Vec<String> = command.time.iter().flat_map(|word| word.split_whitespace()).map(|word| word.to_lowercase()).collect();
+	if words.first().is_some_and(|word| word == "to") {
+		words.remove(0);
+	}
+
+	let when = When::read_all(&words, chrono::Local::now())?;
+	let recurrence = when.recurrence().cloned();
+	let due = when.resolve()?.with_timezone(&chrono::Utc);
+
+	let reminder = utils::update_reminders(|reminders| {
+		let index = utils::find_reminder(reminders, id)?;
+		let reminder = &mut reminders[index];
+		reminder.due = due;
+		if recurrence.is_some() {
+			reminder.recurrence = recurrence;
+		}
+		Ok(reminder.clone())
+	})?;
+
+	println!("📅 {}", "Reminder rescheduled".green());
+	println!("{}", utils::format_listed_reminder(&reminder, chrono::Utc::now()));
+
+	Ok(())
+}
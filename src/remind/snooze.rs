@@ -0,0 +1,96 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::utils;
+use crate::date::When;
+
+#[derive(Args)]
+pub struct Command {
+	/// Id of the reminder, like 3 or #3, or "last" for the reminder that was delivered last
+	id: String,
+
+	/// How long to wait, e.g. 10m, 2h, 1d, "an hour" or "for 20 minutes"
+	duration: Vec<String>
+}
+
+/*
+	A reminder that is still to come is delivered that much later, one that was delivered is delivered again that long from now.
+	Snoozing a recurring reminder adds a reminder that is delivered once, so its other occurrences stay at the same time.
+	The occurrence that is snoozed is the one that was delivered for "last", and the coming one that is listed for an id.
+	The coming one is skipped, so it isn't delivered at its own time as well.
+*/
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let id = utils::parse_id(&command.id)?;
+	let delivered = command.id.eq_ignore_ascii_case("last");
+	let words = to_clause(&command.duration);
+	let now = chrono::Utc::now();
+
+	let reminder = utils::update_storage(|data| {
+		let index = utils::find_reminder(&data.reminders, id)?;
+		let reminder = &data.reminders[index];
+		let coming = reminder.is_pending() && !(delivered && reminder.recurrence.is_some());
+		let from = if coming { reminder.due.max(now) } else { now };
+		let due = When::read_all(&words, from.with_timezone(&chrono::Local))?.resolve()?.with_timezone(&chrono::Utc);
+
+		if reminder.recurrence.is_some() {
+			let mut snoozed = reminder.clone();
+			snoozed.id = data.next_id.take();
+			snoozed.timestamp = now;
+			snoozed.due = due;
+			snoozed.notified = None;
+			snoozed.recurrence = None;
+			if coming {
+				let original = &mut data.reminders[index];
+				if !super::advance(original, original.due) {
+					data.reminders.remove(index);
+				}
+			}
+			data.reminders.push(snoozed.clone());
+			return Ok(snoozed);
+		}
+
+		let reminder = &mut data.reminders[index];
+		reminder.due = due;
+		Ok(reminder.clone())
+	})?;
+
+	println!("😴 {}", "Reminder snoozed".green());
+	println!("{}", utils::format_listed_reminder(&reminder, now));
+
+	Ok(())
+}
+
+// The duration as a time clause, "10m" is read as "in 10 minutes" and "for an hour" as "in an hour"
+fn to_clause(duration: &[String]) -> Vec<String> {
+	let mut words: Vec<String> = duration.iter().flat_map(|word| word.split_whitespace()).map(|word| word.to_lowercase()).collect();
+	if words.is_empty() {
+		words.push("10m".to_string());
+	}
+
+	if let [word] = words.as_slice() {
+		if let Some(expanded) = expand_short(word) {
+			words = expanded;
+		}
+	}
+
+	match words.first().map(|word| word.as_str()) {
+		Some("for" | "in") => words[0] = "in".to_string(),
+		_ => words.insert(0, "in".to_string())
+	}
+
+	words
+}
+
+// "10m", "2h", "1d" and "1w"
+fn expand_short(word: &str) -> Option<Vec<String>> {
+	let (count, unit) = word.split_at(word.find(|c: char| !c.is_ascii_digit())?);
+	let unit = match unit {
+		"m" | "min" | "mins" => "minutes",
+		"h" | "hr" | "hrs" => "hours",
+		"d" => "days",
+		"w" => "weeks",
+		_ => return None
+	};
+
+	(!count.is_empty()).then(|| vec![count.to_string(), unit.to_string()])
+}
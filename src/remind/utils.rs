@@ -0,0 +1,82 @@
+use colored::Colorize;
+
+use super::data::{Reminder, ReminderStorage};
+use crate::{config, date, picker, storage};
+
+pub fn get_reminders() -> anyhow::Result<Vec<Reminder>> {
+	let mut data: ReminderStorage = storage::load("remind")?;
+	if data.next_id.migrate(data.reminders.iter_mut().map(|reminder| &mut reminder.id)) {
+		// Written back so the ids stay the same
+		update_storage(|_| Ok(()))?;
+	}
+
+	Ok(data.reminders)
+}
+
+// Changes the reminders as one transaction
+pub fn update_storage<R>(change: impl FnOnce(&mut ReminderStorage) -> anyhow::Result<R>) -> anyhow::Result<R> {
+	storage::update("remind", |data: &mut ReminderStorage| {
+		data.next_id.migrate(data.reminders.iter_mut().map(|reminder| &mut reminder.id));
+		change(data)
+	})
+}
+
+pub fn update_reminders<R>(change: impl FnOnce(&mut Vec<Reminder>) -> anyhow::Result<R>) -> anyhow::Result<R> {
+	update_storage(|data| change(&mut data.reminders))
+}
+
+pub fn find_reminder(reminders: &[Reminder], id: u64) -> anyhow::Result<usize> {
+	reminders
+		.iter()
+		.position(|reminder| reminder.id == id)
+		.ok_or(anyhow::format_err!("There is no reminder #{}, see 'hey remind list'", id))
+}
+
+// Accepts an id like 3 or #3, or "last" for the reminder that was delivered last
+pub fn parse_id(id: &str) -> anyhow::Result<u64> {
+	if id.eq_ignore_ascii_case("last") {
+		return get_reminders()?
+			.iter()
+			.filter(|reminder| reminder.notified.is_some())
+			.max_by_key(|reminder| reminder.notified)
+			.map(|reminder| reminder.id)
+			.ok_or(anyhow::format_err!("No reminder has been delivered yet"));
+	}
+
+	id.strip_prefix('#').unwrap_or(id).parse().map_err(|_| anyhow::format_err!("'{}' is not a reminder id", id))
+}
+
+// Lets the user pick one of the reminders that are coming up, the action is shown when there are none
+pub fn pick(action: &str) -> anyhow::Result<Option<u64>> {
+	let mut reminders: Vec<Reminder> = get_reminders()?.into_iter().filter(|reminder| reminder.is_pending()).collect();
+	if reminders.is_empty() {
+		println!("📭 Nothing to {}", action);
+		return Ok(None);
+	}
+
+	reminders.sort_by_key(|reminder| reminder.due);
+	let lines: Vec<String> = reminders.iter().map(|reminder| format!("#{} {}", reminder.id, format_reminder(reminder, false))).collect();
+	Ok(picker::pick(&lines)?.map(|idx| reminders[idx].id))
+}
+
+pub fn format_reminder(reminder: &Reminder, colors: bool) -> String {
+	let due = reminder.due.with_timezone(&chrono::Local).format(&format!("%a {}", config::get().timestamp_format())).to_string();
+	let recurrence = reminder.recurrence.as_ref().map(|recurrence| format!(" ({})", recurrence)).unwrap_or_default();
+	if colors {
+		format!("{} {}{}", due.cyan(), reminder.message, recurrence.blue())
+	} else {
+		format!("{} {}{}", due, reminder.message, recurrence)
+	}
+}
+
+// The reminder with its id and how long until it's due, as shown by "hey remind list"
+pub fn format_listed_reminder(reminder: &Reminder, now: chrono::DateTime<chrono::Utc>) -> String {
+	let id = format!("#{}", reminder.id);
+	let relative = format!("({})", date::format_relative(reminder.due, now));
+	let relative = match reminder.is_pending() {
+		true => relative.yellow(),
+		false => relative.dimmed()
+	};
+
+	format!("{} {} {}", id.dimmed(), relative, format_reminder(reminder, true))
+}
@@ -0,0 +1,37 @@
+use colored::Colorize;
+
+// Text matching shared by the searches over todos, lists and notes. Matching never regards case
+pub fn contains(text: &str, query: &str) -> bool {
+	text.to_lowercase().contains(&query.to_lowercase())
+}
+
+// Colors every part of the text that matches one of the words
+pub fn highlight(text: &str, words: &[String]) -> String {
+	let words: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+	let lowercase = text.to_lowercase();
+	if lowercase.len() != text.len() {
+		// Lowercasing changed the byte positions, the matches can not be mapped back to the text
+		return text.to_string();
+	}
+
+	let mut highlighted = String::new();
+
+	let mut idx = 0;
+	while idx < text.len() {
+		let matched = words.iter()
+			.filter(|word| !word.is_empty() && lowercase[idx..].starts_with(word.as_str()) && text.is_char_boundary(idx + word.len()))
+			.map(|word| word.len())
+			.max();
+
+		if let Some(length) = matched {
+			highlighted.push_str(&text[idx..idx + length].yellow().bold().to_string());
+			idx += length;
+		} else {
+			let c = text[idx..].chars().next().unwrap();
+			highlighted.push(c);
+			idx += c.len_utf8();
+		}
+	}
+
+	highlighted
+}
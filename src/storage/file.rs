@@ -0,0 +1,78 @@
+/*
+	Files are written to a temporary file next to them which is then renamed over the old file,
+	so a crash while writing leaves either the old or the new content and never half a file.
+	Changes hold an exclusive lock on "FILE.lock" while reading, changing and writing the file,
+	so two hey commands at the same time can't overwrite each other's changes. The lock is taken on a separate file
+	because the data file itself is replaced on every write.
+	Reads don't need the lock, the rename makes sure a reader sees a whole file.
+*/
+
+use std::{fs::File, io::Write, path::{Path, PathBuf}};
+
+// The lock is released when this is dropped
+pub struct Lock {
+	_file: File
+}
+
+pub fn lock(path: &Path) -> anyhow::Result<Lock> {
+	create_parent(path)?;
+
+	let lock_path = with_suffix(path, "lock");
+	let file = File::options().create(true).truncate(false).write(true).open(&lock_path)?;
+	file.lock().map_err(|err| anyhow::format_err!("Could not lock {}: {}", lock_path.display(), err))?;
+
+	Ok(Lock{_file: file})
+}
+
+// The content of the file, or None if it doesn't exist yet
+pub fn read(path: &Path) -> anyhow::Result<Option<String>> {
+	match std::fs::read_to_string(path) {
+		Ok(content) => Ok(Some(content)),
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+		Err(err) => Err(anyhow::format_err!("Could not read {}: {}", path.display(), err))
+	}
+}
+
+// Replaces the content of the file in one step, the caller should hold the lock of the file
+pub fn write(path: &Path, content: &str) -> anyhow::Result<()> {
+	create_parent(path)?;
+
+	let temporary_path = with_suffix(path, &format!("{}.tmp", std::process::id()));
+	let result = (|| {
+		let mut file = File::create(&temporary_path)?;
+		file.write_all(content.as_bytes())?;
+		file.sync_all()?;
+		std::fs::rename(&temporary_path, path)
+	})();
+
+	if let Err(err) = result {
+		let _ = std::fs::remove_file(&temporary_path);
+		return Err(anyhow::format_err!("Could not write {}: {}", path.display(), err));
+	}
+
+	Ok(())
+}
+
+// Removes the file if it exists, the caller should hold the lock of the file
+pub fn remove(path: &Path) -> anyhow::Result<()> {
+	match std::fs::remove_file(path) {
+		Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(anyhow::format_err!("Could not remove {}: {}", path.display(), err)),
+		_ => Ok(())
+	}
+}
+
+pub fn create_parent(path: &Path) -> anyhow::Result<()> {
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+
+	Ok(())
+}
+
+// "todo.yaml" -> "todo.yaml.lock"
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+	let mut path = path.as_os_str().to_owned();
+	path.push(".");
+	path.push(suffix);
+	PathBuf::from(path)
+}
@@ -0,0 +1,39 @@
+use serde_json::Value;
+
+use super::{file, Backend};
+use crate::paths;
+
+// Every document is a file like todo.json, for scripts and tools that would rather read JSON
+pub struct JsonBackend;
+
+impl JsonBackend {
+	fn get_path(name: &str) -> anyhow::Result<std::path::PathBuf> {
+		paths::get_data_path(&format!("{}.json", name))
+	}
+
+	fn parse(path: &std::path::Path, content: Option<String>) -> anyhow::Result<Option<Value>> {
+		content
+			.map(|content| serde_json::from_str(&content).map_err(|err| anyhow::format_err!("Could not read {}: {}", path.display(), err)))
+			.transpose()
+	}
+}
+
+impl Backend for JsonBackend {
+	fn read(&self, name: &str) -> anyhow::Result<Option<Value>> {
+		let path = Self::get_path(name)?;
+		Self::parse(&path, file::read(&path)?)
+	}
+
+	fn update(&self, name: &str, change: &mut dyn FnMut(Option<Value>) -> anyhow::Result<Value>) -> anyhow::Result<()> {
+		let path = Self::get_path(name)?;
+		let _lock = file::lock(&path)?;
+		let document = change(Self::parse(&path, file::read(&path)?)?)?;
+		file::write(&path, &(serde_json::to_string_pretty(&document)? + "\n"))
+	}
+
+	fn remove(&self, name: &str) -> anyhow::Result<()> {
+		let path = Self::get_path(name)?;
+		let _lock = file::lock(&path)?;
+		file::remove(&path)
+	}
+}
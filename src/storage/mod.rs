@@ -0,0 +1,122 @@
+/*
+	Storage of the todos, reminders and notes, which are each kept as a document with a name like "todo".
+	The backend decides where and how the documents are kept, it is chosen with "storage_format" in the config.
+	Documents are passed to the backends as JSON values, so a backend doesn't need to know what is stored in them.
+
+	Every change is a transaction: the document is read, changed and written while no other hey can change it.
+*/
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::config::{self, StorageFormat};
+
+pub use file::{lock, read, write};
+
+pub trait Backend {
+	// The document, or None if nothing has been stored in it yet
+	fn read(&self, name: &str) -> anyhow::Result<Option<Value>>;
+
+	// Replaces the document with the one returned by change, as a single transaction.
+	// Nothing is written if change returns an error
+	fn update(&self, name: &str, change: &mut dyn FnMut(Option<Value>) -> anyhow::Result<Value>) -> anyhow::Result<()>;
+
+	// Removes the document, reading it afterwards gives None
+	fn remove(&self, name: &str) -> anyhow::Result<()>;
+}
+
+// All documents hey stores
+const DOCUMENTS: [&str; 3] = ["todo", "remind", "note"];
+
+pub fn get_backend() -> Box<dyn Backend> {
+	get_format_backend(config::get().storage_format)
+}
+
+fn get_format_backend(format: StorageFormat) -> Box<dyn Backend> {
+	match format {
+		StorageFormat::Yaml => Box::new(yaml::YamlBackend),
+		StorageFormat::Json => Box::new(json::JsonBackend),
+		StorageFormat::Sqlite => Box::new(sqlite::SqliteBackend)
+	}
+}
+
+// The stored document, or the default if nothing has been stored yet
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> anyhow::Result<T> {
+	match get_backend().read(name)? {
+		Some(value) => from_value(name, value),
+		None => Ok(T::default())
+	}
+}
+
+// Changes the stored document as one transaction
+pub fn update<T, R>(name: &str, change: impl FnOnce(&mut T) -> anyhow::Result<R>) -> anyhow::Result<R>
+where T: DeserializeOwned + Serialize + Default {
+	update_value(name, |value| {
+		let mut data = match value {
+			Some(value) => from_value(name, value)?,
+			None => T::default()
+		};
+		let result = change(&mut data)?;
+		Ok((serde_json::to_value(&data)?, result))
+	})
+}
+
+// Same as update, for documents that need more than deserializing, like documents in an old format
+pub fn update_value<R>(name: &str, change: impl FnOnce(Option<Value>) -> anyhow::Result<(Value, R)>) -> anyhow::Result<R> {
+	let mut change = Some(change);
+	let mut result = None;
+	get_backend().update(name, &mut |value| {
+		let change = change.take().ok_or(anyhow::format_err!("The {} document was changed twice in one transaction", name))?;
+		let (value, changed_result) = change(value)?;
+		result = Some(changed_result);
+		Ok(value)
+	})?;
+
+	result.ok_or(anyhow::format_err!("The {} document was not changed", name))
+}
+
+/*
+	Moves the documents to another format when storage_format is changed, they would be hidden otherwise.
+	Nothing is moved if the other format already has a document that is different, as one of the two would be lost.
+	The documents are only removed from the old format once they are stored in the new one. Returns the names of the moved documents.
+*/
+pub fn migrate(from: StorageFormat, to: StorageFormat) -> anyhow::Result<Vec<&'static str>> {
+	let (source, target) = (get_format_backend(from), get_format_backend(to));
+	let mut documents = Vec::new();
+	for name in DOCUMENTS {
+		let Some(document) = source.read(name)? else {
+			continue;
+		};
+		match target.read(name)? {
+			Some(existing) if existing != document => return Err(anyhow::format_err!(
+				"The {} document is stored as both {} and {}, remove one of them before changing storage_format", name, from, to
+			)),
+			_ => documents.push((name, document))
+		}
+	}
+
+	let mut moved = Vec::new();
+	for (name, document) in documents {
+		target.update(name, &mut |_| Ok(document.clone()))?;
+		if target.read(name)?.as_ref() != Some(&document) {
+			return Err(anyhow::format_err!("The {} document could not be moved to {}, it is still stored as {}", name, to, from));
+		}
+		source.remove(name)?;
+		moved.push(name);
+	}
+
+	Ok(moved)
+}
+
+pub fn load_value(name: &str) -> anyhow::Result<Option<Value>> {
+	get_backend().read(name)
+}
+
+pub fn from_value<T: DeserializeOwned>(name: &str, value: Value) -> anyhow::Result<T> {
+	serde_json::from_value(value).map_err(|err| anyhow::format_err!("Could not read the {} document: {}", name, err))
+}
+
+mod file;
+mod json;
+mod sqlite;
+mod yaml;
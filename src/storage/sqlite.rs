@@ -0,0 +1,310 @@
+/*
+	All documents are kept in hey.db. A document is split into rows, so a change only writes the rows that changed
+	instead of the whole document, which keeps adding to a store with thousands of items fast.
+
+	Every object in an array of objects is a row of its own, recursively. The path of the row says where it belongs,
+	e.g. "/lists/1/todos/3" is the todo with id 3 of a list, and the root of the document has the path "".
+	The last part of the path is a key that stays the same while the item is kept: its id if it has one, otherwise
+	the key of the unchanged row it was read from, or a new key. The order of the items is kept in the position column,
+	so removing an item doesn't move the ones after it to another path, and only the rows that changed are written.
+	In the row of the parent the array is left empty, and is filled from the rows below it when the document is read.
+*/
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use rusqlite::{params, Connection, TransactionBehavior};
+use serde_json::Value;
+
+use super::Backend;
+use crate::paths;
+
+#[derive(PartialEq)]
+struct Row {
+	position: i64,
+	value: String
+}
+
+pub struct SqliteBackend;
+
+impl SqliteBackend {
+	fn open() -> anyhow::Result<Connection> {
+		let path = paths::get_data_path("hey.db")?;
+		super::file::create_parent(&path)?;
+
+		let mut connection = Connection::open(&path).map_err(|err| anyhow::format_err!("Could not open {}: {}", path.display(), err))?;
+		// Another hey holding the write lock is waited for instead of failing right away
+		connection.busy_timeout(std::time::Duration::from_secs(10))?;
+		connection.execute_batch(
+			"CREATE TABLE IF NOT EXISTS records (
+				document TEXT NOT NULL,
+				path TEXT NOT NULL,
+				position INTEGER NOT NULL DEFAULT 0,
+				value TEXT NOT NULL,
+				PRIMARY KEY (document, path)
+			)"
+		)?;
+		Self::add_positions(&mut connection)?;
+
+		Ok(connection)
+	}
+
+	// Databases from before the position column have the index of the item as the key, which becomes its position
+	fn add_positions(connection: &mut Connection) -> anyhow::Result<()> {
+		let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+		let has_positions: bool = transaction.query_row("SELECT COUNT(*) > 0 FROM pragma_table_info('records') WHERE name = 'position'", [], |row| row.get(0))?;
+		if has_positions {
+			return Ok(());
+		}
+
+		transaction.execute("ALTER TABLE records ADD COLUMN position INTEGER NOT NULL DEFAULT 0", [])?;
+		let paths = transaction
+			.prepare("SELECT document, path FROM records")?
+			.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+			.collect::<Result<Vec<_>, _>>()?;
+		for (document, path) in paths {
+			if let Some(Ok(position)) = path.rsplit_once('/').map(|(_, key)| key.parse::<i64>()) {
+				transaction.execute("UPDATE records SET position = ?1 WHERE document = ?2 AND path = ?3", params![position, document, path])?;
+			}
+		}
+
+		transaction.commit()?;
+		Ok(())
+	}
+
+	fn get_rows(connection: &Connection, name: &str) -> anyhow::Result<HashMap<String, Row>> {
+		let mut statement = connection.prepare("SELECT path, position, value FROM records WHERE document = ?1")?;
+		let rows = statement
+			.query_map(params![name], |row| Ok((row.get(0)?, Row{position: row.get(1)?, value: row.get(2)?})))?
+			.collect::<Result<HashMap<String, Row>, _>>()?;
+
+		Ok(rows)
+	}
+}
+
+impl Backend for SqliteBackend {
+	fn read(&self, name: &str) -> anyhow::Result<Option<Value>> {
+		let connection = Self::open()?;
+		let rows = Self::get_rows(&connection, name)?;
+		join("", &rows, &get_children(&rows))
+	}
+
+	fn update(&self, name: &str, change: &mut dyn FnMut(Option<Value>) -> anyhow::Result<Value>) -> anyhow::Result<()> {
+		let mut connection = Self::open()?;
+		// An immediate transaction takes the write lock right away, so no other hey can change the rows in between
+		let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+		let old_rows = Self::get_rows(&transaction, name)?;
+		let old_children = get_children(&old_rows);
+		let document = change(join("", &old_rows, &old_children)?)?;
+		let mut new_rows = HashMap::new();
+		split("", 0, document, &old_rows, &old_children, &mut new_rows);
+
+		for path in old_rows.keys().filter(|path| !new_rows.contains_key(*path)) {
+			transaction.execute("DELETE FROM records WHERE document = ?1 AND path = ?2", params![name, path])?;
+		}
+		for (path, row) in &new_rows {
+			if old_rows.get(path) != Some(row) {
+				transaction.execute(
+					"INSERT OR REPLACE INTO records (document, path, position, value) VALUES (?1, ?2, ?3, ?4)",
+					params![name, path, row.position, row.value]
+				)?;
+			}
+		}
+
+		transaction.commit()?;
+		Ok(())
+	}
+
+	fn remove(&self, name: &str) -> anyhow::Result<()> {
+		Self::open()?.execute("DELETE FROM records WHERE document = ?1", params![name])?;
+		Ok(())
+	}
+}
+
+// The keys of the items of every array, by the path of the array ("/lists/1/todos"), in the order of their positions
+fn get_children(rows: &HashMap<String, Row>) -> HashMap<&str, Vec<&str>> {
+	let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+	for path in rows.keys() {
+		if let Some((array, key)) = path.rsplit_once('/').filter(|(array, _)| !array.is_empty()) {
+			children.entry(array).or_default().push(key);
+		}
+	}
+
+	for (array, keys) in children.iter_mut() {
+		keys.sort_by_key(|key| rows[&format!("{}/{}", array, key)].position);
+	}
+
+	children
+}
+
+fn split(path: &str, position: i64, mut value: Value, old_rows: &HashMap<String, Row>, old_children: &HashMap<&str, Vec<&str>>, rows: &mut HashMap<String, Row>) {
+	if let Value::Object(fields) = &mut value {
+		for (field, child) in fields.iter_mut() {
+			match child {
+				Value::Array(items) if !items.is_empty() && items.iter().all(|item| item.is_object()) => {
+					let array = format!("{}/{}", path, field);
+					let items = std::mem::take(items);
+					let keys = get_keys(&items, old_children.get(array.as_str()).map_or(&[], |keys| keys.as_slice()), &array, old_rows);
+
+					// An item keeps its position while it is still after the one before it, so only moved and new items are written
+					let mut previous = None;
+					for (key, item) in keys.into_iter().zip(items) {
+						let item_path = format!("{}/{}", array, key);
+						let item_position = match (old_rows.get(&item_path).map(|row| row.position), previous) {
+							(Some(old), Some(previous)) if old > previous => old,
+							(Some(old), None) => old,
+							(_, previous) => previous.map_or(0, |previous| previous + 1)
+						};
+						previous = Some(item_position);
+						split(&item_path, item_position, item, old_rows, old_children, rows);
+					}
+				},
+				_ => ()
+			}
+		}
+	}
+
+	rows.insert(path.to_string(), Row{position, value: value.to_string()});
+}
+
+/*
+	The key of each item: its id if it has a unique one, otherwise the key of an old row with the same content.
+	The items left get keys that are not used yet.
+*/
+fn get_keys(items: &[Value], old_keys: &[&str], array: &str, old_rows: &HashMap<String, Row>) -> Vec<String> {
+	let ids: Vec<Option<u64>> = items.iter().map(|item| item.get("id").and_then(Value::as_u64).filter(|id| *id > 0)).collect();
+	let mut counts: HashMap<u64, usize> = HashMap::new();
+	for id in ids.iter().flatten() {
+		*counts.entry(*id).or_default() += 1;
+	}
+
+	let mut keys: Vec<Option<String>> = ids.iter()
+		.map(|id| id.filter(|id| counts[id] == 1).map(|id| id.to_string()))
+		.collect();
+	let mut used: HashSet<String> = keys.iter().flatten().cloned().collect();
+
+	// The old keys by the content of their row, the first one is taken first
+	let mut old_contents: HashMap<&str, VecDeque<&str>> = HashMap::new();
+	for old_key in old_keys.iter().filter(|old_key| !used.contains(**old_key)) {
+		old_contents.entry(old_rows[&format!("{}/{}", array, old_key)].value.as_str()).or_default().push_back(old_key);
+	}
+	for (key, item) in keys.iter_mut().zip(items).filter(|(key, _)| key.is_none()) {
+		*key = old_contents.get_mut(own_value(item).as_str()).and_then(|old_keys| old_keys.pop_front()).map(str::to_string);
+		used.extend(key.clone());
+	}
+
+	used.extend(old_keys.iter().map(|old_key| old_key.to_string()));
+	let mut next_key = 0;
+	keys.into_iter()
+		.map(|key| key.unwrap_or_else(|| {
+			while used.contains(&format!("n{}", next_key)) {
+				next_key += 1;
+			}
+			used.insert(format!("n{}", next_key));
+			format!("n{}", next_key)
+		}))
+		.collect()
+}
+
+// The value of the row of an item, without the items of its arrays of objects
+fn own_value(item: &Value) -> String {
+	let mut item = item.clone();
+	if let Value::Object(fields) = &mut item {
+		for child in fields.values_mut() {
+			if let Value::Array(items) = child {
+				if items.iter().all(|item| item.is_object()) {
+					items.clear();
+				}
+			}
+		}
+	}
+
+	item.to_string()
+}
+
+fn join(path: &str, rows: &HashMap<String, Row>, children: &HashMap<&str, Vec<&str>>) -> anyhow::Result<Option<Value>> {
+	let Some(row) = rows.get(path) else {
+		return Ok(None);
+	};
+
+	let mut value: Value = serde_json::from_str(&row.value)?;
+	if let Value::Object(fields) = &mut value {
+		for (field, child) in fields.iter_mut() {
+			match child {
+				Value::Array(items) if items.is_empty() => {
+					let array = format!("{}/{}", path, field);
+					for key in children.get(array.as_str()).into_iter().flatten() {
+						items.extend(join(&format!("{}/{}", array, key), rows, children)?);
+					}
+				},
+				_ => ()
+			}
+		}
+	}
+
+	Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn rows(document: Value, old_rows: &HashMap<String, Row>) -> HashMap<String, Row> {
+		let mut rows = HashMap::new();
+		split("", 0, document, old_rows, &get_children(old_rows), &mut rows);
+		rows
+	}
+
+	// The paths of the rows that are written when going from the old rows to the new ones
+	fn written(old_rows: &HashMap<String, Row>, new_rows: &HashMap<String, Row>) -> Vec<String> {
+		let mut paths: Vec<String> = new_rows.iter()
+			.filter(|(path, row)| old_rows.get(*path) != Some(*row))
+			.map(|(path, _)| path.clone())
+			.chain(old_rows.keys().filter(|path| !new_rows.contains_key(*path)).cloned())
+			.collect();
+		paths.sort();
+		paths
+	}
+
+	fn todos(ids: &[u64]) -> Value {
+		json!({
+			"next_id": 10,
+			"lists": [{"name": "todo", "todos": ids.iter().map(|id| json!({"id": id, "task": format!("task {}", id)})).collect::<Vec<_>>()}]
+		})
+	}
+
+	#[test]
+	fn documents_are_read_back_in_order() {
+		let document = json!({"lists": [{"name": "b", "todos": [{"id": 3}, {"id": 1}]}, {"name": "a", "todos": []}], "tags": ["x"]});
+		let rows = rows(document.clone(), &HashMap::new());
+
+		assert_eq!(join("", &rows, &get_children(&rows)).unwrap(), Some(document));
+		assert_eq!(join("", &HashMap::new(), &HashMap::new()).unwrap(), None);
+	}
+
+	#[test]
+	fn removing_an_item_only_deletes_its_row() {
+		let old_rows = rows(todos(&[1, 2, 3, 4]), &HashMap::new());
+		let new_rows = rows(todos(&[1, 3, 4]), &old_rows);
+
+		assert_eq!(written(&old_rows, &new_rows), vec!["/lists/n0/todos/2"]);
+	}
+
+	#[test]
+	fn items_without_ids_keep_the_key_of_their_row() {
+		let old_rows = rows(json!({"notes": [{"text": "a"}, {"text": "b"}, {"text": "c"}]}), &HashMap::new());
+		let new_rows = rows(json!({"notes": [{"text": "b"}, {"text": "c"}, {"text": "d"}]}), &old_rows);
+
+		assert_eq!(written(&old_rows, &new_rows), vec!["/notes/n0", "/notes/n3"]);
+		assert_eq!(join("", &new_rows, &get_children(&new_rows)).unwrap(), Some(json!({"notes": [{"text": "b"}, {"text": "c"}, {"text": "d"}]})));
+	}
+
+	#[test]
+	fn moved_items_are_given_a_new_position() {
+		let old_rows = rows(todos(&[1, 2, 3]), &HashMap::new());
+		let new_rows = rows(todos(&[3, 1, 2]), &old_rows);
+
+		assert_eq!(join("", &new_rows, &get_children(&new_rows)).unwrap(), Some(todos(&[3, 1, 2])));
+	}
+}
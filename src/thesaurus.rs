@@ -11,10 +11,12 @@ impl Thesaurus {
 		}
 	}
 
+	// Synonyms are added to the ones the word already has
 	pub fn add_synonyms(&mut self, word: &str, synonyms: Vec<&str>) {
-		self.synonyms.insert(word.to_string(), synonyms.iter().map(|s| s.to_string()).collect());
+		self.synonyms.entry(word.to_string()).or_default().extend(synonyms.iter().map(|s| s.to_string()));
 	}
 
+	#[allow(dead_code)]
 	pub fn is_synonym(&self, word: &str, synonym: &str) -> bool {
         if let Some(synonyms) = self.synonyms.get(word) {
             synonyms.contains(&synonym.to_string())
@@ -23,12 +25,18 @@ impl Thesaurus {
         }
 	}
 
-	pub fn get_synonyms(&self, word: &str) -> &Vec<String> {
-		self.synonyms.get(word).unwrap()
+	pub fn get_synonyms(&self, word: &str) -> &[String] {
+		self.synonyms.get(word).map_or(&[], |synonyms| synonyms.as_slice())
 	}
 }
 
 pub fn add_default_synonyms(thesaurus: &mut Thesaurus) {
 	thesaurus.add_synonyms("called", vec!["named"]);
     thesaurus.add_synonyms("create", vec!["construct", "build", "devise", "design", "establish", "forge", "form", "generate", "initiate", "invent", "make", "produce", "set up", "spawn"]);
+	thesaurus.add_synonyms("add", vec!["put", "append"]);
+	thesaurus.add_synonyms("show", vec!["display", "print", "view"]);
+	thesaurus.add_synonyms("check", vec!["tick", "complete", "finish", "cross"]);
+	thesaurus.add_synonyms("todo", vec!["todos", "task", "tasks"]);
+	thesaurus.add_synonyms("delete", vec!["remove", "erase", "drop"]);
+	thesaurus.add_synonyms("note", vec!["jot", "write"]);
 }
\ No newline at end of file
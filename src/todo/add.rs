@@ -2,26 +2,71 @@ use clap::Args;
 
 use colored::Colorize;
 
-use super::{data::Todo, utils};
+use super::{data::{Priority, Todo}, utils};
+use crate::{date, recurrence};
 
 #[derive(Args)]
 pub struct Command {
+	/// The list to add the todo to, the default list is used if not given
+	#[arg(short, long)]
+	list: Option<String>,
+
+	/// Can also be given in the task as "!high"
+	#[arg(short, long)]
+	priority: Option<Priority>,
+
+	/// The day the todo is due, e.g. friday, tomorrow or 18/10/24. Can also be given in the task as "due:friday"
+	#[arg(long)]
+	due: Option<String>,
+
+	/// How often the todo comes back once checked, e.g. monday, "2 weeks" or "month until 31/12/24".
+	/// Can also be given in the task as "every monday"
+	#[arg(long, value_name = "RULE")]
+	every: Option<String>,
+
+	/// A tag for the todo, can be given more than once. Can also be given in the task as "#work"
+	#[arg(short, long = "tag")]
+	tags: Vec<String>,
+
 	task: Vec<String>
 }
 
 pub fn execute(command: Command) -> anyhow::Result<()> {
-	let task = command.task.join(" ");
-	add(&task)?;
+	let mut todo = Todo::new(String::new());
+	utils::parse_task(&command.task, &mut todo)?;
 
-	println!("📝 {}", "Todo added!".green());
+	// Flags win over details written in the task
+	todo.priority = command.priority.or(todo.priority);
+	if let Some(due) = &command.due {
+		todo.due = Some(date::parse_day(due)?);
+	}
+	if let Some(every) = &command.every {
+		todo.recurrence = Some(recurrence::parse_rule(every)?);
+	}
+	for tag in command.tags {
+		if !todo.tags.contains(&tag) {
+			todo.tags.push(tag);
+		}
+	}
 
-	Ok(())
-}
+	// A recurring todo without a due date is due at its first occurrence, "water the plants every monday"
+	if let (None, Some(recurrence)) = (todo.due, &todo.recurrence) {
+		todo.due = recurrence.first_on_or_after(chrono::Local::now().date_naive());
+	}
+
+	let list = command.list.unwrap_or_else(utils::default_list);
+	add(&list, todo)?;
 
-fn add(task: &str) -> anyhow::Result<()> {
-	let mut todos = utils::get_todos()?;
-	todos.push(Todo{timestamp: chrono::Utc::now(), task: task.to_string()});
-	utils::write_todos(todos)?;
+	println!("📝 {}", format!("Todo added to {}!", list).green());
 
 	Ok(())
-}
\ No newline at end of file
+}
+
+fn add(list: &str, mut todo: Todo) -> anyhow::Result<()> {
+	utils::update_storage(|data| {
+		let index = utils::find_list(&data.lists, list)?;
+		todo.id = data.next_id.take();
+		data.lists[index].todos.push(todo);
+		Ok(())
+	})
+}
@@ -1,60 +1,106 @@
 use clap::Args;
-use std::io::Write;
 
 use super::{data::Todo, utils};
+use crate::{picker, search};
 
 #[derive(Args)]
-pub struct Command {}
+pub struct Command {
+	/// The list to check a todo in, the default list is used if not given. An id is looked up in all lists unless this is given
+	#[arg(short, long)]
+	list: Option<String>,
 
-pub fn execute(_command: Command) -> anyhow::Result<()> {
-	let todos = utils::get_todos()?;
+	/// Position of the todo in the list, starting at 1
+	#[arg(short, long, conflicts_with = "todo")]
+	index: Option<usize>,
+
+	/// Id of the todo, like 12 or #12, or text that only one todo in the list contains.
+	/// A todo is picked interactively if neither this nor an index is given
+	todo: Vec<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let todo = command.todo.join(" ");
+	if let Some(id) = parse_id(&todo) {
+		return complete(id, command.list.as_deref());
+	}
+
+	let list = command.list.unwrap_or_else(utils::default_list);
+	let todos = utils::get_todos(&list)?;
 	if todos.is_empty() {
 		println!("📭 Nothing to check");
 		return Ok(());
 	}
 
-	if let Some(selection) = get_selection(&todos) {
-		remove(&selection, todos)?;
-	}
+	let id = if let Some(index) = command.index {
+		todos.get(index.wrapping_sub(1)).ok_or(anyhow::format_err!("There is no todo at position {} in {}", index, list))?.id
+	} else if !todo.is_empty() {
+		find_by_text(&todos, &todo)?
+	} else {
+		let lines: Vec<String> = todos.iter().map(|todo| utils::format_todo(todo, false)).collect();
+		match picker::pick(&lines)? {
+			Some(idx) => todos[idx].id,
+			None => return Ok(())
+		}
+	};
 
-	Ok(())
+	complete(id, Some(&list))
+}
+
+fn parse_id(todo: &str) -> Option<u64> {
+	todo.strip_prefix('#').unwrap_or(todo).parse().ok()
 }
 
-fn get_selection(todos: &Vec<Todo>) -> Option<String> {
-	let mut fzf = std::process::Command::new("fzf")
-		.stdin(std::process::Stdio::piped())
-		.stdout(std::process::Stdio::piped())
-		.spawn()
-		.expect("Failed to start fzf");
-
-	{
-		let stdin = fzf.stdin.as_mut().expect("Failed to open stdin");
-		for todo in todos {
-			writeln!(stdin, "{}", utils::format_todo(&todo, false)).expect("Failed to write to stdin");
+fn find_by_text(todos: &[Todo], text: &str) -> anyhow::Result<u64> {
+	let matches: Vec<&Todo> = todos.iter().filter(|todo| search::contains(&todo.task, text)).collect();
+	match matches.as_slice() {
+		[todo] => Ok(todo.id),
+		[] => Err(anyhow::format_err!("No todo contains '{}'", text)),
+		_ => {
+			let lines: Vec<String> = matches.iter().map(|todo| format!("  {}", utils::format_todo(todo, false))).collect();
+			Err(anyhow::format_err!("More than one todo contains '{}', use the id instead:\n{}", text, lines.join("\n")))
 		}
 	}
+}
 
-	let output = fzf
-		.wait_with_output()
-		.expect("Failed to read fzf output");
+// A recurring todo is added again with the date of its next occurrence, the todo has to be in the list if one is given
+fn complete(id: u64, list: Option<&str>) -> anyhow::Result<()> {
+	let (task, next) = utils::update_storage(|data| {
+		let (list_idx, todo_idx) = utils::find_todo(&data.lists, id)?;
+		if let Some(list) = list {
+			if utils::find_list(&data.lists, list)? != list_idx {
+				return Err(anyhow::format_err!("There is no todo with the id {} in {}, it is in {}", id, list, data.lists[list_idx].name));
+			}
+		}
+		let todo = &mut data.lists[list_idx].todos[todo_idx];
+		if todo.is_done() {
+			return Err(anyhow::format_err!("'{}' is already done, restore it with 'hey todo uncheck {}'", todo.task, id));
+		}
 
-	if output.status.success() {
-		let selection = String::from_utf8_lossy(&output.stdout);
-		Some(selection.trim().to_string())
-	} else {
-		None
-	}
-}
+		let completed = chrono::Utc::now();
+		todo.completed = Some(completed);
+		let task = todo.task.clone();
+		/*
+			The next one is added at the time the todo was completed, which is how uncheck finds it again.
+			It is not added twice when the todo is checked after an uncheck left the next one in the list.
+		*/
+		let next = todo.next_occurrence()
+			.filter(|next| !data.lists[list_idx].todos.iter().any(|other| !other.is_done() && utils::is_same_occurrence(other, next)))
+			.map(|mut next| {
+				next.id = data.next_id.take();
+				next.timestamp = completed;
+				let line = utils::format_todo(&next, true);
+				data.lists[list_idx].todos.push(next);
+				line
+			});
 
-fn remove(selection: &str, mut todos: Vec<Todo>) -> anyhow::Result<()> {
-	// TODO: Check that the correct item is being removed, an index has to be used instead of a raw string
-	if let Some(index) = todos.iter().position(|todo| utils::format_todo(todo, false) == selection) {
-		let todo = todos.remove(index);
-		utils::write_todos(todos)?;
+		Ok((task, next))
+	})?;
 
-		println!("✅ Marked '{}' as done", todo.task);
-		Ok(())
-	} else {
-		Err(anyhow::format_err!("Nothing selected"))
+	println!("✅ Marked '{}' as done", task);
+	if let Some(next) = next {
+		println!("🔁 Next one added");
+		println!("{}", next);
 	}
-}
\ No newline at end of file
+
+	Ok(())
+}
@@ -0,0 +1,27 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::{data::TodoList, utils};
+
+#[derive(Args)]
+pub struct Command {
+	/// Name of the new list, it can contain spaces
+	#[arg(required = true)]
+	name: Vec<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let name = command.name.join(" ");
+	utils::update_lists(|lists| {
+		if utils::find_list(lists, &name).is_ok() {
+			return Err(anyhow::format_err!("A list called '{}' already exists", name));
+		}
+
+		lists.push(TodoList{name: name.clone(), todos: Vec::new()});
+		Ok(())
+	})?;
+
+	println!("🗒️ {}", format!("Created the list {}!", name).green());
+
+	Ok(())
+}
@@ -1,10 +1,119 @@
+use crate::{ids::NextId, recurrence::Recurrence};
+
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct TodoStorage {
+	// The id given to the next todo, ids are never reused
+	#[serde(default)]
+	pub next_id: NextId,
+	pub lists: Vec<TodoList>
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TodoList {
+	pub name: String,
 	pub todos: Vec<Todo>
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct Todo {
+	// Unique over all lists, 0 is only used for todos written before ids existed
+	#[serde(default)]
+	pub id: u64,
 	pub timestamp: chrono::DateTime<chrono::Utc>,
-	pub task: String
-}
\ No newline at end of file
+	pub task: String,
+	// When the todo was checked, checked todos are kept as history
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub completed: Option<chrono::DateTime<chrono::Utc>>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub priority: Option<Priority>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub due: Option<chrono::NaiveDate>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+	// A recurring todo is added again when it is checked
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub recurrence: Option<Recurrence>
+}
+
+impl Todo {
+	// A new todo without details, the id is given when it is stored
+	pub fn new(task: String) -> Self {
+		Todo {
+			id: 0,
+			timestamp: chrono::Utc::now(),
+			task,
+			completed: None,
+			priority: None,
+			due: None,
+			tags: Vec::new(),
+			recurrence: None
+		}
+	}
+
+	// The todo that comes after a recurring todo is checked, due at the first occurrence after its due date that is still to come
+	pub fn next_occurrence(&self) -> Option<Todo> {
+		let today = chrono::Local::now().date_naive();
+		let recurrence = self.recurrence.as_ref()?.anchored_on(self.due.unwrap_or(today));
+		let due = recurrence.next_after_skipping(self.due.unwrap_or(today), today)?;
+
+		Some(Todo {
+			id: 0,
+			timestamp: chrono::Utc::now(),
+			task: self.task.clone(),
+			completed: None,
+			priority: self.priority,
+			due: Some(due),
+			tags: self.tags.clone(),
+			recurrence: Some(recurrence)
+		})
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.completed.is_some()
+	}
+
+	pub fn is_overdue(&self) -> bool {
+		!self.is_done() && self.due.is_some_and(|due| due < chrono::Local::now().date_naive())
+	}
+
+	// Todos without a priority are sorted as medium priority
+	pub fn priority_or_default(&self) -> Priority {
+		self.priority.unwrap_or(Priority::Medium)
+	}
+}
+
+#[derive(serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+	Low,
+	Medium,
+	High
+}
+
+impl Priority {
+	// Inline priorities are written as "!high", "!med" or "!low"
+	pub fn parse(word: &str) -> Option<Self> {
+		match word.to_lowercase().as_str() {
+			"low" => Some(Priority::Low),
+			"medium" | "med" => Some(Priority::Medium),
+			"high" => Some(Priority::High),
+			_ => None
+		}
+	}
+}
+
+impl std::fmt::Display for Priority {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Priority::Low => write!(f, "low"),
+			Priority::Medium => write!(f, "medium"),
+			Priority::High => write!(f, "high")
+		}
+	}
+}
+
+// The format from before named lists, a single list that is migrated to the default list
+#[derive(serde::Deserialize)]
+pub struct LegacyTodoStorage {
+	pub todos: Vec<Todo>
+}
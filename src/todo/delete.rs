@@ -0,0 +1,27 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::utils;
+
+#[derive(Args)]
+pub struct Command {
+	/// Name of the list to delete, together with all of its todos
+	#[arg(required = true)]
+	name: Vec<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let name = command.name.join(" ");
+	let list = utils::update_lists(|lists| {
+		let index = utils::find_list(lists, &name)?;
+		if utils::is_default_list(&lists[index].name) {
+			return Err(anyhow::format_err!("The default list '{}' cannot be deleted, change default_list in the config first", lists[index].name));
+		}
+
+		Ok(lists.remove(index))
+	})?;
+
+	println!("🗑️ {}", format!("Deleted the list {} and its {} todo(s)", list.name, list.todos.len()).green());
+
+	Ok(())
+}
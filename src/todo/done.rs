@@ -0,0 +1,57 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::utils;
+use crate::date;
+
+#[derive(Args)]
+pub struct Command {
+	/// Only show todos done in this list
+	#[arg(short, long)]
+	list: Option<String>,
+
+	/// Only show todos done on or after this date, e.g. 18/10/24, 2024-10-18 or yesterday
+	#[arg(long)]
+	from: Option<String>,
+
+	/// Only show todos done on or before this date
+	#[arg(long)]
+	to: Option<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let from = command.from.as_deref().map(date::parse_date).transpose()?;
+	let to = command.to.as_deref().map(date::parse_date).transpose()?;
+
+	let mut lists = utils::get_lists()?;
+	if let Some(list) = &command.list {
+		let index = utils::find_list(&lists, list)?;
+		lists = vec![lists.swap_remove(index)];
+	}
+
+	let mut found = false;
+	for list in lists {
+		let mut todos: Vec<_> = list.todos
+			.iter()
+			.filter(|todo| todo.completed.is_some_and(|completed| date::is_between(&completed, from, to)))
+			.collect();
+		if todos.is_empty() {
+			continue;
+		}
+
+		// Oldest first, like the todos are shown
+		todos.sort_by_key(|todo| todo.completed);
+
+		found = true;
+		println!("{}", list.name.bold());
+		for todo in todos {
+			println!("  {}", utils::format_done_todo(todo, true));
+		}
+	}
+
+	if !found {
+		println!("📭 Nothing has been done yet");
+	}
+
+	Ok(())
+}
@@ -0,0 +1,173 @@
+use std::io::Write;
+
+use clap::Args;
+
+use super::{data::{Priority, Todo}, utils};
+use crate::{date, editor, picker, recurrence};
+
+#[derive(Args)]
+pub struct Command {
+	/// The list to pick a todo from, the default list is used if not given
+	#[arg(short, long)]
+	list: Option<String>,
+
+	/// The new task, details like "#work" or "!high" in it are read like with add
+	#[arg(long, value_name = "TASK", num_args = 1..)]
+	task: Vec<String>,
+
+	#[arg(short, long)]
+	priority: Option<Priority>,
+
+	/// The day the todo is due, e.g. friday, tomorrow or 18/10/24
+	#[arg(long)]
+	due: Option<String>,
+
+	/// Remove the due date
+	#[arg(long, conflicts_with = "due")]
+	no_due: bool,
+
+	/// How often the todo comes back, e.g. monday, "2 weeks" or "month until 31/12/24"
+	#[arg(long, value_name = "RULE")]
+	every: Option<String>,
+
+	/// Stop the todo from coming back
+	#[arg(long, conflicts_with = "every")]
+	no_every: bool,
+
+	/// Add a tag, can be given more than once
+	#[arg(short, long = "tag")]
+	tags: Vec<String>,
+
+	/// Remove a tag, can be given more than once
+	#[arg(long = "untag", value_name = "TAG")]
+	untags: Vec<String>,
+
+	/// Id of the todo, like 12 or #12. A todo is picked interactively if not given.
+	/// The todo is opened in $EDITOR if no new values are given
+	id: Option<String>
+}
+
+impl Command {
+	fn has_values(&self) -> bool {
+		!self.task.is_empty() || self.priority.is_some() || self.due.is_some() || self.no_due || self.every.is_some() || self.no_every || !self.tags.is_empty() || !self.untags.is_empty()
+	}
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let id = match &command.id {
+		Some(id) => id.strip_prefix('#').unwrap_or(id).parse().map_err(|_| anyhow::format_err!("'{}' is not a todo id", id))?,
+		None => match pick(&command.list.clone().unwrap_or_else(utils::default_list))? {
+			Some(id) => id,
+			None => return Ok(())
+		}
+	};
+
+	// The editor is opened before the transaction, so other commands are not blocked while editing
+	let text = match command.has_values() {
+		true => None,
+		false => {
+			let lists = utils::get_lists()?;
+			let (list_idx, todo_idx) = utils::find_todo(&lists, id)?;
+			Some(open_in_editor(&utils::format_task_inline(&lists[list_idx].todos[todo_idx]))?)
+		}
+	};
+
+	let line = utils::update_lists(|lists| {
+		let (list_idx, todo_idx) = utils::find_todo(lists, id)?;
+		let todo = &mut lists[list_idx].todos[todo_idx];
+		let before = utils::format_task_inline(todo);
+
+		match text {
+			Some(text) => {
+				todo.priority = None;
+				todo.due = None;
+				todo.tags.clear();
+				todo.recurrence = None;
+				utils::parse_task(&[text], todo)?;
+			},
+			None => apply_values(command, todo)?
+		}
+
+		Ok((utils::format_task_inline(todo) != before).then(|| utils::format_todo(todo, true)))
+	})?;
+
+	match line {
+		Some(line) => {
+			println!("✏️ Todo updated");
+			println!("{}", line);
+		},
+		None => println!("📭 Nothing was changed")
+	}
+
+	Ok(())
+}
+
+fn apply_values(command: Command, todo: &mut Todo) -> anyhow::Result<()> {
+	if !command.task.is_empty() {
+		utils::parse_task(&command.task, todo)?;
+	}
+
+	if let Some(priority) = command.priority {
+		todo.priority = Some(priority);
+	}
+
+	if let Some(due) = &command.due {
+		todo.due = Some(date::parse_day(due)?);
+	} else if command.no_due {
+		todo.due = None;
+	}
+
+	if let Some(every) = &command.every {
+		todo.recurrence = Some(recurrence::parse_rule(every)?);
+	} else if command.no_every {
+		todo.recurrence = None;
+	}
+
+	todo.tags.retain(|tag| !command.untags.iter().any(|untag| untag.eq_ignore_ascii_case(tag)));
+	for tag in command.tags {
+		if !todo.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+			todo.tags.push(tag);
+		}
+	}
+
+	Ok(())
+}
+
+// Writes the text to a temporary file, lets the user edit it and returns the lines that were written joined together
+fn open_in_editor(text: &str) -> anyhow::Result<String> {
+	/*
+		The temporary directory is shared with other users, create_new fails instead of following a file or symlink that
+		is already at the path.
+	*/
+	let path = std::env::temp_dir().join(format!("hey-todo-{}.txt", std::process::id()));
+	let mut file = std::fs::OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.open(&path)
+		.map_err(|err| anyhow::format_err!("Could not create {}: {}", path.display(), err))?;
+	writeln!(file, "{}", text)?;
+	drop(file);
+
+	let edited = editor::edit_file(&path);
+	let content = std::fs::read_to_string(&path);
+	std::fs::remove_file(&path)?;
+	edited.map_err(|err| anyhow::format_err!("{}, the todo was not changed", err))?;
+
+	let text = content?.lines().map(|line| line.trim()).filter(|line| !line.is_empty()).collect::<Vec<_>>().join(" ");
+	if text.is_empty() {
+		return Err(anyhow::format_err!("The todo was left empty, use 'hey todo check' to remove it"));
+	}
+
+	Ok(text)
+}
+
+fn pick(list: &str) -> anyhow::Result<Option<u64>> {
+	let todos = utils::get_todos(list)?;
+	if todos.is_empty() {
+		println!("📭 Nothing to edit");
+		return Ok(None);
+	}
+
+	let lines: Vec<String> = todos.iter().map(|todo| utils::format_todo(todo, false)).collect();
+	Ok(picker::pick(&lines)?.map(|idx| todos[idx].id))
+}
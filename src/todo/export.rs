@@ -0,0 +1,42 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::{data::TodoList, formats::{self, Format}, utils};
+use crate::storage;
+
+#[derive(Args)]
+pub struct Command {
+	#[arg(short, long, value_enum, default_value_t = Format::Md)]
+	format: Format,
+
+	/// Only export this list, all lists are exported if not given
+	#[arg(short, long)]
+	list: Option<String>,
+
+	/// The file to write to, the todos are printed if not given
+	#[arg(short, long)]
+	output: Option<std::path::PathBuf>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let mut lists = utils::get_lists()?;
+	if let Some(list) = &command.list {
+		let index = utils::find_list(&lists, list)?;
+		lists = vec![lists.swap_remove(index)];
+	}
+
+	let content = formats::write(command.format, &lists)?;
+	match &command.output {
+		Some(path) => {
+			storage::write(path, &content)?;
+			println!("📤 {}", format!("Exported {} todo(s) to {}", count(&lists), path.display()).green());
+		},
+		None => print!("{}", content)
+	}
+
+	Ok(())
+}
+
+fn count(lists: &[TodoList]) -> usize {
+	lists.iter().map(|list| list.todos.len()).sum()
+}
@@ -0,0 +1,364 @@
+/*
+	Reading and writing todo lists in the formats of other tools.
+
+	Markdown: a heading per list and a checklist item per todo, like "- [x] buy milk !high due:2024-10-18 #shopping".
+		The times the todo was added and done are kept in a comment at the end of the line, which is hidden when rendered.
+		Words of the task that would be read as a detail, like "#general" in "post in #general", are written with a "\" in front, which markdown doesn't show.
+	todo.txt: a line per todo as described on http://todotxt.org, the list is the +project and tags are @contexts.
+		The format only has dates, so the times of the day are lost. Recurrences are kept as "rec:2w" when they can be written that way.
+		Words of the task like "+1" or "@home" are written with a "\" in front, so they aren't read as the project or a context.
+	CSV: a row per todo with a header, tags are separated by spaces and the recurrence is written like "every monday".
+	JSON: the lists as they are stored by hey.
+*/
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::{data::{Priority, Todo, TodoList}, utils};
+use crate::recurrence::{self, Recurrence, Unit};
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum Format {
+	Md,
+	Todotxt,
+	Csv,
+	Json
+}
+
+impl Format {
+	// The format of a file, from its extension
+	pub fn from_path(path: &std::path::Path) -> Option<Self> {
+		match path.extension()?.to_str()?.to_lowercase().as_str() {
+			"md" | "markdown" => Some(Format::Md),
+			"txt" | "todotxt" => Some(Format::Todotxt),
+			"csv" => Some(Format::Csv),
+			"json" => Some(Format::Json),
+			_ => None
+		}
+	}
+}
+
+pub fn write(format: Format, lists: &[TodoList]) -> anyhow::Result<String> {
+	match format {
+		Format::Md => Ok(write_markdown(lists)),
+		Format::Todotxt => Ok(write_todotxt(lists)),
+		Format::Csv => write_csv(lists),
+		Format::Json => Ok(serde_json::to_string_pretty(lists)? + "\n")
+	}
+}
+
+// The lists in the content, the ids of the todos are not read since they are given again when the todos are stored
+pub fn read(format: Format, content: &str) -> anyhow::Result<Vec<TodoList>> {
+	match format {
+		Format::Md => read_markdown(content),
+		Format::Todotxt => read_todotxt(content),
+		Format::Csv => read_csv(content),
+		Format::Json => Ok(serde_json::from_str(content)?)
+	}
+}
+
+fn write_markdown(lists: &[TodoList]) -> String {
+	let mut sections = Vec::new();
+	for list in lists {
+		let mut section = format!("# {}\n\n", list.name);
+		for todo in &list.todos {
+			let check = if todo.is_done() { "x" } else { " " };
+			let mut times = format!("added:{}", todo.timestamp.to_rfc3339());
+			if let Some(completed) = todo.completed {
+				times += &format!(" done:{}", completed.to_rfc3339());
+			}
+			section += &format!("- [{}] {} <!-- {} -->\n", check, utils::format_task_inline(todo), times);
+		}
+		sections.push(section);
+	}
+
+	sections.join("\n")
+}
+
+fn read_markdown(content: &str) -> anyhow::Result<Vec<TodoList>> {
+	let mut lists: Vec<TodoList> = Vec::new();
+
+	for (idx, line) in content.lines().enumerate() {
+		let line = line.trim();
+		if let Some(name) = line.strip_prefix('#').map(|heading| heading.trim_start_matches('#').trim()) {
+			lists.push(TodoList{name: name.to_string(), todos: Vec::new()});
+			continue;
+		}
+
+		let Some((done, item)) = ["- [ ] ", "* [ ] "].iter().find_map(|prefix| line.strip_prefix(prefix).map(|item| (false, item)))
+			.or_else(|| ["- [x] ", "- [X] ", "* [x] ", "* [X] "].iter().find_map(|prefix| line.strip_prefix(prefix).map(|item| (true, item))))
+		else {
+			continue;
+		};
+
+		let (item, comment) = match item.split_once("<!--") {
+			Some((item, comment)) => (item, comment.trim_end().trim_end_matches("-->")),
+			None => (item, "")
+		};
+
+		let mut todo = Todo::new(String::new());
+		utils::parse_task(&[item.to_string()], &mut todo).map_err(|err| anyhow::format_err!("Line {}: {}", idx + 1, err))?;
+		for word in comment.split_whitespace() {
+			if let Some(added) = word.strip_prefix("added:") {
+				todo.timestamp = parse_timestamp(added).map_err(|err| anyhow::format_err!("Line {}: {}", idx + 1, err))?;
+			} else if let Some(completed) = word.strip_prefix("done:") {
+				todo.completed = Some(parse_timestamp(completed).map_err(|err| anyhow::format_err!("Line {}: {}", idx + 1, err))?);
+			}
+		}
+		if done && todo.completed.is_none() {
+			todo.completed = Some(Utc::now());
+		}
+
+		// Items before the first heading belong to the default list
+		if lists.is_empty() {
+			lists.push(TodoList{name: utils::default_list(), todos: Vec::new()});
+		}
+		lists.last_mut().unwrap().todos.push(todo);
+	}
+
+	Ok(lists)
+}
+
+fn write_todotxt(lists: &[TodoList]) -> String {
+	let mut lines = Vec::new();
+	for list in lists {
+		for todo in &list.todos {
+			let mut words = Vec::new();
+			let priority = todo.priority.map(|priority| match priority {
+				Priority::High => "A",
+				Priority::Medium => "B",
+				Priority::Low => "C"
+			});
+
+			// Done todos don't have a priority in front, it is kept as "pri:A" instead
+			match (todo.completed, priority) {
+				(Some(completed), _) => words.push(format!("x {}", to_date(&completed))),
+				(None, Some(priority)) => words.push(format!("({})", priority)),
+				(None, None) => ()
+			}
+			words.push(to_date(&todo.timestamp).to_string());
+			words.push(escape_todotxt(&todo.task));
+			words.push(format!("+{}", list.name.replace(' ', "_")));
+			words.extend(todo.tags.iter().map(|tag| format!("@{}", tag)));
+			words.extend(todo.due.map(|due| format!("due:{}", due.format("%Y-%m-%d"))));
+			words.extend(todo.recurrence.as_ref().and_then(write_rec));
+			if todo.is_done() {
+				words.extend(priority.map(|priority| format!("pri:{}", priority)));
+			}
+
+			lines.push(words.join(" "));
+		}
+	}
+
+	lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn read_todotxt(content: &str) -> anyhow::Result<Vec<TodoList>> {
+	let mut lists: Vec<TodoList> = Vec::new();
+
+	for (idx, line) in content.lines().enumerate() {
+		let mut words = line.split_whitespace().peekable();
+		if words.peek().is_none() {
+			continue;
+		}
+
+		let mut todo = Todo::new(String::new());
+		if words.next_if_eq(&"x").is_some() {
+			todo.completed = Some(words.next_if(|word| parse_date(word).is_some()).and_then(parse_date).unwrap_or_else(Utc::now));
+		}
+		if let Some(priority) = words.next_if(|word| parse_priority(word.trim_start_matches('(').trim_end_matches(')')).is_some() && word.starts_with('(')) {
+			todo.priority = parse_priority(priority.trim_start_matches('(').trim_end_matches(')'));
+		}
+		if let Some(added) = words.next_if(|word| parse_date(word).is_some()).and_then(parse_date) {
+			todo.timestamp = added;
+		}
+
+		let mut list = None;
+		let mut task = Vec::new();
+		for word in words {
+			if let Some(literal) = word.strip_prefix('\\').filter(|literal| !literal.is_empty()) {
+				task.push(literal);
+			} else if let Some(project) = word.strip_prefix('+').filter(|project| !project.is_empty() && list.is_none()) {
+				list = Some(project.replace('_', " "));
+			} else if let Some(context) = word.strip_prefix('@').filter(|context| !context.is_empty()) {
+				todo.tags.push(context.to_string());
+			} else if let Some(due) = word.strip_prefix("due:") {
+				todo.due = Some(NaiveDate::parse_from_str(due, "%Y-%m-%d").map_err(|_| anyhow::format_err!("Line {}: '{}' is not a date like 2024-10-18", idx + 1, due))?);
+			} else if let Some(priority) = word.strip_prefix("pri:").and_then(parse_priority) {
+				todo.priority = Some(priority);
+			} else if let Some(recurrence) = word.strip_prefix("rec:").and_then(read_rec) {
+				todo.recurrence = Some(recurrence);
+			} else {
+				task.push(word);
+			}
+		}
+
+		if task.is_empty() {
+			return Err(anyhow::format_err!("Line {} has no task", idx + 1));
+		}
+		todo.task = task.join(" ");
+
+		let list = list.unwrap_or_else(utils::default_list);
+		match lists.iter_mut().find(|other| other.name.eq_ignore_ascii_case(&list)) {
+			Some(other) => other.todos.push(todo),
+			None => lists.push(TodoList{name: list, todos: vec![todo]})
+		}
+	}
+
+	Ok(lists)
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CsvRow {
+	list: String,
+	task: String,
+	#[serde(default)]
+	priority: Option<Priority>,
+	#[serde(default)]
+	due: Option<NaiveDate>,
+	#[serde(default)]
+	tags: String,
+	#[serde(default)]
+	every: String,
+	#[serde(default)]
+	added: Option<DateTime<Utc>>,
+	#[serde(default)]
+	done: Option<DateTime<Utc>>
+}
+
+fn write_csv(lists: &[TodoList]) -> anyhow::Result<String> {
+	let mut writer = csv::Writer::from_writer(Vec::new());
+	for list in lists {
+		for todo in &list.todos {
+			writer.serialize(CsvRow{
+				list: list.name.clone(),
+				task: todo.task.clone(),
+				priority: todo.priority,
+				due: todo.due,
+				tags: todo.tags.join(" "),
+				every: todo.recurrence.as_ref().map(|recurrence| recurrence.to_string()).unwrap_or_default(),
+				added: Some(todo.timestamp),
+				done: todo.completed
+			})?;
+		}
+	}
+
+	Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn read_csv(content: &str) -> anyhow::Result<Vec<TodoList>> {
+	let mut lists: Vec<TodoList> = Vec::new();
+	let mut reader = csv::Reader::from_reader(content.as_bytes());
+
+	for row in reader.deserialize() {
+		let row: CsvRow = row?;
+		let mut todo = Todo::new(row.task);
+		todo.timestamp = row.added.unwrap_or(todo.timestamp);
+		todo.completed = row.done;
+		todo.priority = row.priority;
+		todo.due = row.due;
+		todo.tags = row.tags.split_whitespace().map(|tag| tag.to_string()).collect();
+		if !row.every.is_empty() {
+			todo.recurrence = Some(recurrence::parse_rule(&row.every)?);
+		}
+
+		let list = if row.list.is_empty() { utils::default_list() } else { row.list };
+		match lists.iter_mut().find(|other| other.name.eq_ignore_ascii_case(&list)) {
+			Some(other) => other.todos.push(todo),
+			None => lists.push(TodoList{name: list, todos: vec![todo]})
+		}
+	}
+
+	Ok(lists)
+}
+
+// Words of the task that would be read as a project, context or other detail get a "\" in front, like in markdown
+fn escape_todotxt(task: &str) -> String {
+	task.split_whitespace()
+		.map(|word| if ["\\", "+", "@", "due:", "pri:", "rec:"].iter().any(|prefix| word.starts_with(prefix)) {
+			format!("\\{}", word)
+		} else {
+			word.to_string()
+		})
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+// The "rec:" extension of todo.txt, "rec:1d", "rec:2w", "rec:1m" or "rec:1b" for every weekday
+fn write_rec(recurrence: &Recurrence) -> Option<String> {
+	let unit = match recurrence.unit {
+		Unit::Day => "d",
+		Unit::Week if recurrence.weekdays.is_empty() => "w",
+		Unit::Week if recurrence.every == 1 && recurrence.weekdays == recurrence::WEEKDAYS => "b",
+		Unit::Week => return None,
+		Unit::Month => "m"
+	};
+
+	Some(format!("rec:{}{}", recurrence.every, unit))
+}
+
+// A leading "+" means the next date is counted from the due date instead of the completion date, hey always uses the due date
+fn read_rec(rec: &str) -> Option<Recurrence> {
+	let rec = rec.trim_start_matches('+');
+	let (count, unit) = rec.split_at(rec.len().checked_sub(1)?);
+	let count: u32 = if count.is_empty() { 1 } else { count.parse().ok().filter(|count| *count > 0)? };
+	let rule = match unit {
+		"d" => format!("{} days", count),
+		"w" => format!("{} weeks", count),
+		"m" => format!("{} months", count),
+		"y" => format!("{} months", count.checked_mul(12)?),
+		"b" if count == 1 => "weekday".to_string(),
+		_ => return None
+	};
+
+	recurrence::parse_rule(&rule).ok()
+}
+
+// todo.txt uses A to Z, everything after C is read as low
+fn parse_priority(letter: &str) -> Option<Priority> {
+	match letter {
+		"A" => Some(Priority::High),
+		"B" => Some(Priority::Medium),
+		letter if letter.len() == 1 && letter.chars().all(|c| c.is_ascii_uppercase()) => Some(Priority::Low),
+		_ => None
+	}
+}
+
+// A todo.txt date, read as the start of that day in the local timezone
+fn parse_date(word: &str) -> Option<DateTime<Utc>> {
+	let date = NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()?;
+	date.and_hms_opt(0, 0, 0)?.and_local_timezone(chrono::Local).earliest().map(|time| time.with_timezone(&Utc))
+}
+
+fn to_date(timestamp: &DateTime<Utc>) -> chrono::format::DelayedFormat<chrono::format::StrftimeItems<'static>> {
+	timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d")
+}
+
+fn parse_timestamp(timestamp: &str) -> anyhow::Result<DateTime<Utc>> {
+	DateTime::parse_from_rfc3339(timestamp)
+		.map(|time| time.with_timezone(&Utc))
+		.map_err(|_| anyhow::format_err!("'{}' is not a time like 2024-10-18T17:30:00+00:00", timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn round_trip(format: Format, tasks: &[&str]) -> Vec<String> {
+		let todos = tasks.iter().map(|task| Todo::new(task.to_string())).collect();
+		let lists = vec![TodoList{name: "todo".to_string(), todos}];
+		let lists = read(format, &write(format, &lists).unwrap()).unwrap();
+		lists[0].todos.iter().map(|todo| todo.task.clone()).collect()
+	}
+
+	const TASKS: [&str; 4] = ["post in #general", "read every day for !high scores", "ask about due:dates", "use \\n and @home +1"];
+
+	#[test]
+	fn markdown_keeps_task_words_that_look_like_details() {
+		assert_eq!(round_trip(Format::Md, &TASKS), TASKS);
+	}
+
+	#[test]
+	fn todotxt_keeps_task_words_that_look_like_details() {
+		assert_eq!(round_trip(Format::Todotxt, &TASKS), TASKS);
+	}
+}
@@ -0,0 +1,88 @@
+use std::io::Read;
+
+use clap::Args;
+use colored::Colorize;
+
+use super::{data::TodoList, formats::{self, Format}, utils};
+
+#[derive(Args)]
+pub struct Command {
+	/// Format of the file, read from the extension of the file if not given
+	#[arg(short, long, value_enum)]
+	format: Option<Format>,
+
+	/// Import every todo into this list, instead of the lists named in the file
+	#[arg(short, long)]
+	list: Option<String>,
+
+	/// The file to import, "-" reads from stdin
+	file: std::path::PathBuf
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let format = command.format
+		.or_else(|| Format::from_path(&command.file))
+		.ok_or(anyhow::format_err!("Could not tell the format of {} from its extension, give it with --format", command.file.display()))?;
+
+	let content = if command.file.as_os_str() == "-" {
+		let mut content = String::new();
+		std::io::stdin().read_to_string(&mut content)?;
+		content
+	} else {
+		std::fs::read_to_string(&command.file).map_err(|err| anyhow::format_err!("Could not read {}: {}", command.file.display(), err))?
+	};
+
+	let mut imported = formats::read(format, &content)?;
+	if let Some(list) = command.list {
+		let todos = imported.into_iter().flat_map(|list| list.todos).collect();
+		imported = vec![TodoList{name: list, todos}];
+	}
+
+	let (added, skipped) = import(imported)?;
+	println!("📥 {}", format!("Imported {} todo(s)", added).green());
+	if skipped > 0 {
+		println!("Skipped {} todo(s) that were already in the list", skipped);
+	}
+
+	Ok(())
+}
+
+/*
+	Adds the todos to the lists with the same name, lists that don't exist yet are created. An underscore in a name matches a space.
+	A todo is skipped if its list already has a todo with the same task and due date, so importing the same file twice adds nothing.
+	The due date tells the occurrences of a recurring todo apart.
+	Returns the amount of todos added and skipped.
+*/
+fn import(imported: Vec<TodoList>) -> anyhow::Result<(usize, usize)> {
+	utils::update_storage(|data| {
+		let (mut added, mut skipped) = (0, 0);
+		for list in imported {
+			let index = match utils::find_list(&data.lists, &list.name).ok().or_else(|| find_unescaped_list(&data.lists, &list.name)) {
+				Some(index) => index,
+				None => {
+					data.lists.push(TodoList{name: list.name, todos: Vec::new()});
+					data.lists.len() - 1
+				}
+			};
+
+			for mut todo in list.todos {
+				if data.lists[index].todos.iter().any(|other| utils::is_same_occurrence(other, &todo)) {
+					skipped += 1;
+					continue;
+				}
+
+				todo.id = data.next_id.take();
+				data.lists[index].todos.push(todo);
+				added += 1;
+			}
+		}
+
+		Ok((added, skipped))
+	})
+}
+
+// todo.txt writes the spaces of a list name as "_", so "+my_list" is read as "my list" and has to find the list "my_list" again
+fn find_unescaped_list(lists: &[TodoList], name: &str) -> Option<usize> {
+	let unescaped = |name: &str| name.to_lowercase().replace('_', " ");
+	lists.iter().position(|list| unescaped(&list.name) == unescaped(name))
+}
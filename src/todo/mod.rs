@@ -10,7 +10,19 @@ pub struct Command {
 enum Commands {
 	Add(add::Command),
 	Show(show::Command),
-	Check(check::Command)
+	Check(check::Command),
+	Uncheck(uncheck::Command),
+	Edit(edit::Command),
+	/// Show the todos that have been done, e.g. what got done yesterday
+	#[command(visible_alias = "history")]
+	Done(done::Command),
+	Create(create::Command),
+	Delete(delete::Command),
+	Rename(rename::Command),
+	/// Write the todos as Markdown, todo.txt, CSV or JSON
+	Export(export::Command),
+	/// Add the todos from a Markdown, todo.txt, CSV or JSON file, todos that already exist are skipped
+	Import(import::Command)
 }
 
 pub fn execute(command: Command) -> anyhow::Result<()> {
@@ -18,11 +30,45 @@ pub fn execute(command: Command) -> anyhow::Result<()> {
 		Commands::Add(input) => add::execute(input),
 		Commands::Show(input) => show::execute(input),
 		Commands::Check(input) => check::execute(input),
+		Commands::Uncheck(input) => uncheck::execute(input),
+		Commands::Edit(input) => edit::execute(input),
+		Commands::Done(input) => done::execute(input),
+		Commands::Create(input) => create::execute(input),
+		Commands::Delete(input) => delete::execute(input),
+		Commands::Rename(input) => rename::execute(input),
+		Commands::Export(input) => export::execute(input),
+		Commands::Import(input) => import::execute(input),
 	}
 }
 
+pub use data::{Priority, Todo};
+pub use utils::default_list;
+
+// Names of all lists, used by the free text parser to recognize list names with spaces
+pub fn get_list_names() -> anyhow::Result<Vec<String>> {
+	Ok(utils::get_lists()?.into_iter().map(|list| list.name).collect())
+}
+
+// The todos that are not done and have a due date, they are exported to calendars with the reminders
+pub fn get_due_todos() -> anyhow::Result<Vec<Todo>> {
+	Ok(utils::get_lists()?
+		.into_iter()
+		.flat_map(|list| list.todos)
+		.filter(|todo| !todo.is_done() && todo.due.is_some())
+		.collect())
+}
+
 mod add;
 mod show;
 mod check;
+mod uncheck;
+mod edit;
+mod done;
+mod create;
+mod delete;
+mod rename;
+mod export;
+mod import;
+mod formats;
 mod data;
-mod utils;
\ No newline at end of file
+mod utils;
@@ -0,0 +1,36 @@
+use clap::Args;
+use colored::Colorize;
+
+use super::utils;
+
+#[derive(Args)]
+pub struct Command {
+	/// Current name of the list
+	#[arg(required = true)]
+	name: Vec<String>,
+
+	/// New name of the list
+	#[arg(long, required = true, num_args = 1..)]
+	to: Vec<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let name = command.name.join(" ");
+	let new_name = command.to.join(" ");
+	let old_name = utils::update_lists(|lists| {
+		let index = utils::find_list(lists, &name)?;
+
+		if utils::is_default_list(&lists[index].name) {
+			return Err(anyhow::format_err!("The default list '{}' cannot be renamed, change default_list in the config first", lists[index].name));
+		}
+		if utils::find_list(lists, &new_name).is_ok_and(|other| other != index) {
+			return Err(anyhow::format_err!("A list called '{}' already exists", new_name));
+		}
+
+		Ok(std::mem::replace(&mut lists[index].name, new_name.clone()))
+	})?;
+
+	println!("✏️ {}", format!("Renamed the list {} to {}", old_name, new_name).green());
+
+	Ok(())
+}
@@ -1,16 +1,115 @@
 use clap::Args;
+use colored::Colorize;
 
-use super::utils;
+use super::{data::{Priority, Todo}, utils};
+use crate::{date, note, search};
 
 #[derive(Args)]
-pub struct Command {}
+pub struct Command {
+	/// Show the available lists instead of the items of a list
+	#[arg(long)]
+	lists: bool,
 
-pub fn execute(_command: Command) -> anyhow::Result<()> {
-	let todos = utils::get_todos()?;
+	/// Show every list and note containing the text, grouped by where it was found
+	#[arg(long = "where", value_name = "TEXT", num_args = 1..)]
+	filter: Vec<String>,
+
+	/// Order of the todos, they are shown in the order they were added by default
+	#[arg(long, value_enum, default_value_t = Sort::Added)]
+	sort: Sort,
+
+	/// Only show todos with this tag
+	#[arg(short, long)]
+	tag: Option<String>,
+
+	/// Only show todos with at least this priority, todos without a priority count as medium
+	#[arg(short, long)]
+	priority: Option<Priority>,
+
+	/// Only show todos due on or before this day, e.g. today, friday or 18/10/24
+	#[arg(long)]
+	due: Option<String>,
+
+	/// The list to show, the default list is used if not given
+	list: Vec<String>
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum Sort {
+	Added,
+	Priority,
+	Due
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	if !command.filter.is_empty() {
+		return show_where(&command.filter.join(" "));
+	}
+
+	if command.lists {
+		for list in utils::get_lists()? {
+			println!("{} ({})", list.name.bold(), list.todos.iter().filter(|todo| !todo.is_done()).count());
+		}
+
+		return Ok(());
+	}
+
+	let due = command.due.as_deref().map(date::parse_day).transpose()?;
+	let mut todos: Vec<Todo> = utils::get_todos(&utils::get_list_name(&command.list))?
+		.into_iter()
+		.filter(|todo| command.tag.as_ref().is_none_or(|tag| todo.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))))
+		.filter(|todo| command.priority.is_none_or(|priority| todo.priority_or_default() >= priority))
+		.filter(|todo| due.is_none_or(|due| todo.due.is_some_and(|todo_due| todo_due <= due)))
+		.collect();
+
+	// The sorts are stable, so todos that are equal stay in the order they were added
+	match command.sort {
+		Sort::Added => (),
+		Sort::Priority => todos.sort_by_key(|todo| std::cmp::Reverse(todo.priority_or_default())),
+		Sort::Due => todos.sort_by_key(|todo| (todo.due.is_none(), todo.due))
+	}
+
+	if todos.is_empty() {
+		println!("📭 Nothing to show");
+	}
 
 	for todo in todos {
 		println!("{}", utils::format_todo(&todo, true));
 	}
 
 	Ok(())
-}
\ No newline at end of file
+}
+
+// A list is shown if its name or any of its todos contain the text, notes are grouped together last
+fn show_where(text: &str) -> anyhow::Result<()> {
+	let words = vec![text.to_string()];
+	let mut found = false;
+
+	for list in utils::get_lists()? {
+		let todos: Vec<_> = list.todos.iter().filter(|todo| !todo.is_done() && search::contains(&todo.task, text)).collect();
+		if todos.is_empty() && !search::contains(&list.name, text) {
+			continue;
+		}
+
+		found = true;
+		println!("{}", search::highlight(&list.name, &words).bold());
+		for todo in todos {
+			println!("  {}", utils::format_todo_task(todo, &search::highlight(&todo.task, &words), true));
+		}
+	}
+
+	let notes = note::find_notes(text)?;
+	if !notes.is_empty() {
+		found = true;
+		println!("{}", "notes".bold());
+		for note in notes {
+			println!("  {}", note);
+		}
+	}
+
+	if !found {
+		println!("📭 Nothing contains '{}'", text);
+	}
+
+	Ok(())
+}
@@ -0,0 +1,65 @@
+use clap::Args;
+
+use super::utils;
+use crate::picker;
+
+#[derive(Args)]
+pub struct Command {
+	/// The list to pick a done todo from, the default list is used if not given
+	#[arg(short, long)]
+	list: Option<String>,
+
+	/// Id of the done todo, like 12 or #12. A done todo is picked interactively if not given
+	id: Option<String>
+}
+
+pub fn execute(command: Command) -> anyhow::Result<()> {
+	let id = match &command.id {
+		Some(id) => id.strip_prefix('#').unwrap_or(id).parse().map_err(|_| anyhow::format_err!("'{}' is not a todo id", id))?,
+		None => match pick(&command.list.unwrap_or_else(utils::default_list))? {
+			Some(id) => id,
+			None => return Ok(())
+		}
+	};
+
+	let (task, removed) = utils::update_lists(|lists| {
+		let (list_idx, todo_idx) = utils::find_todo(lists, id)?;
+		let todo = &mut lists[list_idx].todos[todo_idx];
+		if !todo.is_done() {
+			return Err(anyhow::format_err!("'{}' is not done yet", todo.task));
+		}
+
+		let completed = todo.completed.take();
+		let task = todo.task.clone();
+
+		// The next one of a recurring todo was added when it was checked, it is removed again as long as it is still open
+		let todos = &mut lists[list_idx].todos;
+		let next_idx = todos.iter().position(|other| {
+			other.id != id && !other.is_done() && other.recurrence.is_some() && Some(other.timestamp) == completed && other.task == task
+		});
+		let removed = next_idx.map(|idx| todos.remove(idx));
+
+		Ok((task, removed))
+	})?;
+
+	println!("↩️ Restored '{}'", task);
+	if let Some(removed) = removed {
+		println!("🔁 Removed the next one again");
+		println!("{}", utils::format_todo(&removed, true));
+	}
+	Ok(())
+}
+
+// Done todos are picked with the most recently done first
+fn pick(list: &str) -> anyhow::Result<Option<u64>> {
+	let lists = utils::get_lists()?;
+	let mut todos: Vec<_> = lists[utils::find_list(&lists, list)?].todos.iter().filter(|todo| todo.is_done()).collect();
+	if todos.is_empty() {
+		println!("📭 Nothing in {} is done", list);
+		return Ok(None);
+	}
+
+	todos.sort_by_key(|todo| std::cmp::Reverse(todo.completed));
+	let lines: Vec<String> = todos.iter().map(|todo| utils::format_done_todo(todo, false)).collect();
+	Ok(picker::pick(&lines)?.map(|idx| todos[idx].id))
+}
@@ -1,49 +1,240 @@
 use colored::Colorize;
 
-use super::data::{self, TodoStorage, Todo};
-use crate::paths::{self};
+use super::data::{LegacyTodoStorage, Priority, Todo, TodoList, TodoStorage};
+use crate::{config, date, ids::NextId, recurrence, storage};
 
-pub fn get_todos() -> anyhow::Result<Vec<data::Todo>> {
-	create_file_if_needed()?;
+pub fn get_storage() -> anyhow::Result<TodoStorage> {
+	let (data, migrated) = read_storage()?;
+	if migrated {
+		// Written back so the ids stay the same
+		update_storage(|_| Ok(()))?;
+	}
 
-	let content = std::fs::read_to_string(paths::get_todo_path())?;
-	let data: TodoStorage = serde_yaml::from_str(&content)?;
+	Ok(data)
+}
 
-	Ok(data.todos)
+// Changes the todos as one transaction
+pub fn update_storage<R>(change: impl FnOnce(&mut TodoStorage) -> anyhow::Result<R>) -> anyhow::Result<R> {
+	storage::update_value("todo", |value| {
+		let (mut data, _) = parse_storage(value)?;
+		let result = change(&mut data)?;
+		Ok((serde_json::to_value(&data)?, result))
+	})
 }
 
-pub fn write_todos(todos: Vec<Todo>) -> anyhow::Result<()> {
-	create_file_if_needed()?;
+pub fn get_lists() -> anyhow::Result<Vec<TodoList>> {
+	Ok(get_storage()?.lists)
+}
 
-	let content = serde_yaml::to_string(&TodoStorage{todos: todos})?;
-	std::fs::write(paths::get_todo_path(), content)?;
+pub fn update_lists<R>(change: impl FnOnce(&mut Vec<TodoList>) -> anyhow::Result<R>) -> anyhow::Result<R> {
+	update_storage(|data| change(&mut data.lists))
+}
 
-	Ok(())
+// The todos of the list that are not done yet
+pub fn get_todos(list: &str) -> anyhow::Result<Vec<Todo>> {
+	let mut lists = get_lists()?;
+	let index = find_list(&lists, list)?;
+
+	Ok(lists.swap_remove(index).todos.into_iter().filter(|todo| !todo.is_done()).collect())
+}
+
+// Finds the list and position of the todo with the id
+pub fn find_todo(lists: &[TodoList], id: u64) -> anyhow::Result<(usize, usize)> {
+	lists
+		.iter()
+		.enumerate()
+		.find_map(|(list_idx, list)| list.todos.iter().position(|todo| todo.id == id).map(|todo_idx| (list_idx, todo_idx)))
+		.ok_or(anyhow::format_err!("There is no todo with the id {}", id))
+}
+
+// List names are compared without case, "Tool Ideas" and "tool ideas" is the same list
+pub fn find_list(lists: &[TodoList], name: &str) -> anyhow::Result<usize> {
+	lists
+		.iter()
+		.position(|list| list.name.to_lowercase() == name.to_lowercase())
+		.ok_or(anyhow::format_err!("There is no list called '{}', create it with 'hey todo create {}'", name, name))
+}
+
+// The list used when no list is given, set with "default_list" in the config
+pub fn default_list() -> String {
+	config::get().default_list.clone()
+}
+
+pub fn is_default_list(name: &str) -> bool {
+	name.to_lowercase() == default_list().to_lowercase()
+}
+
+// The same task due on the same day, which is how the occurrences of a recurring todo are told apart
+pub fn is_same_occurrence(todo: &Todo, other: &Todo) -> bool {
+	todo.task.to_lowercase() == other.task.to_lowercase() && todo.due == other.due
+}
+
+// An empty name from the command line means the default list
+pub fn get_list_name(name: &[String]) -> String {
+	if name.is_empty() {
+		default_list()
+	} else {
+		name.join(" ")
+	}
 }
 
 pub fn format_todo(todo: &Todo, colors: bool) -> String {
-	if colors {
-		format!("{} {}", todo.timestamp.with_timezone(&chrono::Local).format("%d/%m/%y %H:%M").to_string().cyan(), todo.task)
+	format_todo_task(todo, &todo.task, colors)
+}
+
+// Same as format_todo, but with a task that has been changed, e.g. with highlighted search matches
+pub fn format_todo_task(todo: &Todo, task: &str, colors: bool) -> String {
+	let id = format!("#{}", todo.id);
+	let timestamp = todo.timestamp.with_timezone(&chrono::Local).format(&config::get().timestamp_format()).to_string();
+	let mut line = if !colors {
+		format!("{} {} {}", id, timestamp, task)
+	} else if todo.priority == Some(Priority::High) {
+		format!("{} {} {}", id.dimmed(), timestamp.cyan(), task.red().bold())
 	} else {
-		format!("{} {}", todo.timestamp.with_timezone(&chrono::Local).format("%d/%m/%y %H:%M"), todo.task)
+		format!("{} {} {}", id.dimmed(), timestamp.cyan(), task)
+	};
+
+	if let Some(priority) = todo.priority {
+		let priority = format!("!{}", priority);
+		line += " ";
+		line += &match (colors, todo.priority) {
+			(false, _) => priority,
+			(true, Some(Priority::High)) => priority.red().bold().to_string(),
+			(true, _) => priority.yellow().to_string()
+		};
+	}
+
+	if let Some(due) = todo.due {
+		let due = format!("due {}", due.format(&config::get().date_format));
+		line += " ";
+		line += &match (colors, todo.is_overdue()) {
+			(false, _) => due,
+			(true, true) => format!("{} (overdue)", due).red().bold().to_string(),
+			(true, false) => due.yellow().to_string()
+		};
+	}
+
+	if let Some(recurrence) = &todo.recurrence {
+		let recurrence = format!("({})", recurrence);
+		line += " ";
+		line += &if colors { recurrence.blue().to_string() } else { recurrence };
+	}
+
+	for tag in &todo.tags {
+		let tag = format!("#{}", tag);
+		line += " ";
+		line += &if colors { tag.magenta().to_string() } else { tag };
 	}
+
+	line
 }
 
-fn create_file_if_needed() -> anyhow::Result<()> {
-	let path = paths::get_todo_path();
-	if !path.exists() {
-		if let Some(parent) = path.parent() {
-			std::fs::create_dir_all(parent)?;
+/*
+	Reads the inline details of a task, e.g. "buy milk #shopping !high due:friday every week".
+	Tags are written as "#tag", the priority as "!low", "!med" or "!high", the due date as "due:DATE" and the recurrence as "every ...".
+	The remaining words become the task, words that look similar but aren't details, like "issue #12" or "every chapter", are kept.
+	A word with a "\" in front is always part of the task, "\#shopping list" is the task "#shopping list".
+*/
+pub fn parse_task(words: &[String], todo: &mut Todo) -> anyhow::Result<()> {
+	let words: Vec<&str> = words.iter().flat_map(|w| w.split(' ')).collect();
+	let today = chrono::Local::now().date_naive();
+	let mut task = Vec::new();
+	let mut idx = 0;
+	while let Some(&word) = words.get(idx) {
+		idx += 1;
+		if let Some(literal) = word.strip_prefix('\\').filter(|literal| !literal.is_empty()) {
+			task.push(literal);
+		} else if let Some((recurrence, consumed)) = recurrence::parse(&words[idx - 1..], today) {
+			todo.recurrence = Some(recurrence);
+			idx += consumed - 1;
+		} else if let Some(tag) = parse_tag(word) {
+			if !todo.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+				todo.tags.push(tag.to_string());
+			}
+		} else if let Some(priority) = word.strip_prefix('!').and_then(Priority::parse) {
+			todo.priority = Some(priority);
+		} else if let Some(due) = word.strip_prefix("due:") {
+			todo.due = Some(date::parse_day(due)?);
+		} else if !word.is_empty() {
+			task.push(word);
 		}
+	}
 
-		// Create the file
-		std::fs::File::create(&path)?;
-
-		// Write initial data
-		let todos = Vec::new();
-		let content = serde_yaml::to_string(&TodoStorage{todos: todos})?;
-		std::fs::write(paths::get_todo_path(), content)?;
+	if task.is_empty() {
+		return Err(anyhow::format_err!("The todo has no task, only details like tags or a priority"));
 	}
 
+	todo.task = task.join(" ");
 	Ok(())
-}
\ No newline at end of file
+}
+
+// "#shopping" is a tag, "#12" is not
+fn parse_tag(word: &str) -> Option<&str> {
+	word.strip_prefix('#').filter(|tag| !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()))
+}
+
+// The task with a "\" in front of the words parse_task would read as a detail, so it reads back as the same task
+pub fn escape_task(task: &str) -> String {
+	let words: Vec<&str> = task.split(' ').collect();
+	let today = chrono::Local::now().date_naive();
+	let is_detail = |idx: usize, word: &str| word.starts_with('\\')
+		|| parse_tag(word).is_some()
+		|| word.strip_prefix('!').and_then(Priority::parse).is_some()
+		|| word.starts_with("due:")
+		|| recurrence::parse(&words[idx..], today).is_some();
+
+	words.iter()
+		.enumerate()
+		.map(|(idx, word)| if is_detail(idx, word) { format!("\\{}", word) } else { word.to_string() })
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+// The task with its details written inline, the reverse of parse_task
+pub fn format_task_inline(todo: &Todo) -> String {
+	let mut words = vec![escape_task(&todo.task)];
+	words.extend(todo.priority.map(|priority| format!("!{}", priority)));
+	words.extend(todo.due.map(|due| format!("due:{}", due.format("%Y-%m-%d"))));
+	words.extend(todo.recurrence.as_ref().map(|recurrence| recurrence.to_string()));
+	words.extend(todo.tags.iter().map(|tag| format!("#{}", tag)));
+
+	words.join(" ")
+}
+
+// A done todo is shown with the time it was completed instead of when it was added
+pub fn format_done_todo(todo: &Todo, colors: bool) -> String {
+	let id = format!("#{}", todo.id);
+	let completed = todo.completed.unwrap_or(todo.timestamp).with_timezone(&chrono::Local).format(&config::get().timestamp_format()).to_string();
+	if colors {
+		format!("{} {} {}", id.dimmed(), completed.green(), todo.task)
+	} else {
+		format!("{} {} {}", id, completed, todo.task)
+	}
+}
+
+fn read_storage() -> anyhow::Result<(TodoStorage, bool)> {
+	parse_storage(storage::load_value("todo")?)
+}
+
+// The stored todos and whether they had to be migrated
+fn parse_storage(value: Option<serde_json::Value>) -> anyhow::Result<(TodoStorage, bool)> {
+	let mut data = match value {
+		Some(value) => match serde_json::from_value::<TodoStorage>(value.clone()) {
+			Ok(data) => data,
+			Err(_) => {
+				// Files written before named lists existed only have a single list, which becomes the default list
+				let legacy: LegacyTodoStorage = storage::from_value("todo", value)?;
+				TodoStorage{next_id: NextId::default(), lists: vec![TodoList{name: default_list(), todos: legacy.todos}]}
+			}
+		},
+		None => TodoStorage{next_id: NextId::default(), lists: Vec::new()}
+	};
+
+	// The default list always exists, also after "default_list" is changed in the config
+	if !data.lists.iter().any(|list| is_default_list(&list.name)) {
+		data.lists.push(TodoList{name: default_list(), todos: Vec::new()});
+	}
+
+	let migrated = data.next_id.migrate(data.lists.iter_mut().flat_map(|list| &mut list.todos).map(|todo| &mut todo.id));
+	Ok((data, migrated))
+}
@@ -3,6 +3,7 @@
 	Name should be in SCREAMING_SNAKE_CASE
 	Words contain a list of words that define the token. Wildcard token can be created using an "*".
 		This token then consumes all words that don't match any other token.
+		A word can also be a phrase with spaces, like "tool ideas", which becomes a single token.
 */
 pub struct TokenDefinition {
 	name: String,
@@ -41,7 +42,7 @@ pub struct Tokenizer {
 impl Tokenizer {
 	pub fn new(token_definitions: Vec<TokenDefinition>) -> Self {
 		Tokenizer {
-			token_definitions: token_definitions
+			token_definitions
 		}
 	}
 
@@ -54,47 +55,55 @@ impl Tokenizer {
 			return None;
 		}
 
-		for word in str.as_ref().split(' ') {
-			let mut tokenized = false;
+		let words: Vec<&str> = str.as_ref().split(' ').collect();
+		let mut idx = 0;
+		while idx < words.len() {
+			// The longest match decides the token, and the first definition wins when they are equally long.
+			// A word is never tokenized twice
+			let mut best_match: Option<(&TokenDefinition, usize)> = None;
 			for token_definition in &self.token_definitions {
-				if self.is_word_match(word, &token_definition) {
-					tokenized = true;
-					tokens.push(Token{
-						name: token_definition.name.clone(),
-						value: word.to_string()
-					});
+				if let Some(length) = self.get_match_length(&words[idx..], token_definition) {
+					if best_match.is_none_or(|(_, best_length)| length > best_length) {
+						best_match = Some((token_definition, length));
+					}
 				}
 			}
 
-			if !tokenized {
-				if let Some(wildcard_token_def) = wildcard_token_def {
-					tokens.push(Token{
-						name: wildcard_token_def.name.clone(),
-						value: word.to_string()
-					})
-				} else {
-					println!("[Error] Found word '{}' which doesn't match any token, and there is no wildcard token available", word);
-					return None;
-				}
+			if let Some((token_definition, length)) = best_match {
+				tokens.push(Token{
+					name: token_definition.name.clone(),
+					value: words[idx..idx + length].join(" ")
+				});
+				idx += length;
+			} else if let Some(wildcard_token_def) = wildcard_token_def {
+				tokens.push(Token{
+					name: wildcard_token_def.name.clone(),
+					value: words[idx].to_string()
+				});
+				idx += 1;
+			} else {
+				println!("[Error] Found word '{}' which doesn't match any token, and there is no wildcard token available", words[idx]);
+				return None;
 			}
 		}
 
 		Some(tokens)
 	}
 
-	fn is_word_match(&self, word: &str, token_definition: &TokenDefinition) -> bool {
+	// The number of words matched from the start of the words. Regexes match a single word while the words can be phrases, like "set up"
+	fn get_match_length(&self, words: &[&str], token_definition: &TokenDefinition) -> Option<usize> {
 		if let Some(pattern) = &token_definition.regex {
-			if pattern.is_match(word) {
-				return true;
+			if pattern.is_match(words.first()?) {
+				return Some(1);
 			}
-			return false;
-		} else if let Some(words) = &token_definition.words {
-			for pattern_word in words {
-				if word == pattern_word {
-					return true;
-				}
-			}
-			return false;
+			return None;
+		} else if let Some(pattern_words) = &token_definition.words {
+			return pattern_words
+				.iter()
+				.filter(|pattern_word| *pattern_word != "*")
+				.map(|pattern_word| pattern_word.split(' ').count())
+				.filter(|length| *length <= words.len() && pattern_words.contains(&words[..*length].join(" ").to_lowercase()))
+				.max();
 		}
 
 		panic!("[Error] Token definition '{}' did not have words nor regex to match", token_definition.name);
@@ -106,7 +115,7 @@ impl Tokenizer {
 			if let Some(words) = &token_definition.words {
 				for word in words {
 					if word == "*" && definition.is_none() {
-						definition = Some(&token_definition);
+						definition = Some(token_definition);
 					} else if word == "*" && definition.is_some() {
 						println!("[Warn] More than one defintion has been added for the wildcard '*'. This is not allowed and can cause unintended side effects.");
 						println!("[Warn] Already found TokenDefinition {}, also found {}", definition.unwrap().name, token_definition.name);
@@ -116,6 +125,6 @@ impl Tokenizer {
 			}
 		}
 
-		return definition;
+		definition
 	}
 }
\ No newline at end of file